@@ -0,0 +1,256 @@
+use std::env;
+use std::fs;
+use std::io::{self, BufWriter, Read, Write};
+use std::process;
+
+use serde::ser::{Serialize, SerializeMap, Serializer};
+
+// def
+use def_parser::{parse_def, Def};
+
+const USAGE: &str = "usage: def2json [--sections NAME,...] [--pretty] [FILE]
+
+Reads a DEF file, or stdin when FILE is missing or -, and writes it as JSON.
+
+  --sections NAME,...  only these sections, ie. components,nets
+  --pretty             indented output instead of one line";
+
+// section names accepted by --sections: the DEF keyword and the key in the JSON output
+const SECTIONS: &[(&str, &str)] = &[
+    ("version", "version"),
+    ("dividerchar", "divider_char"),
+    ("busbitchars", "busbit_chars"),
+    ("design", "config"),
+    ("vias", "vias"),
+    ("styles", "styles"),
+    ("nondefaultrules", "ndrs"),
+    ("regions", "regions"),
+    ("componentmaskshift", "component_mask_shift"),
+    ("components", "components"),
+    ("pins", "pins"),
+    ("pinproperties", "pin_properties"),
+    ("blockages", "blockages"),
+    ("slots", "slots"),
+    ("fills", "fills"),
+    ("specialnets", "special_nets"),
+    ("nets", "nets"),
+    ("scanchains", "scan_chains"),
+    ("groups", "groups"),
+    ("beginext", "extensions"),
+];
+
+#[derive(Debug, Default, PartialEq)]
+struct Options {
+    sections: Option<Vec<&'static str>>, // JSON keys to keep, None for all
+    pretty: bool,
+    file: Option<String>, // None for stdin
+}
+
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut options = Options::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--sections" => match args.next() {
+                Some(n) => options.sections = Some(section_keys(n)?),
+                None => return Err("--sections needs a list of sections".to_string()),
+            },
+            "--pretty" => options.pretty = true,
+            "--compact" => options.pretty = false,
+            "-" => options.file = None,
+            n if n.starts_with("--sections=") => {
+                options.sections = Some(section_keys(&n["--sections=".len()..])?)
+            }
+            n if n.starts_with('-') => return Err(format!("unknown option `{}`", n)),
+            n if options.file.is_some() => return Err(format!("unexpected argument `{}`", n)),
+            n => options.file = Some(n.to_string()),
+        }
+    }
+    Ok(options)
+}
+
+// JSON keys of a comma separated list of sections, ie. "components,specialnets". A section
+// named twice is kept once, so each key is written once.
+fn section_keys(list: &str) -> Result<Vec<&'static str>, String> {
+    let mut keys = Vec::new();
+    for name in list.split(',').filter(|n| !n.is_empty()) {
+        let name = name.to_ascii_lowercase();
+        let key = SECTIONS
+            .iter()
+            .find(|(keyword, key)| *keyword == name || *key == name)
+            .map(|(_, key)| *key)
+            .ok_or_else(|| {
+                let names: Vec<&str> = SECTIONS.iter().map(|n| n.0).collect();
+                format!(
+                    "unknown section `{}`, expected one of {}",
+                    name,
+                    names.join(", ")
+                )
+            })?;
+        if !keys.contains(&key) {
+            keys.push(key);
+        }
+    }
+    Ok(keys)
+}
+
+// the sections of a Def with the given keys, in the order of the Def fields
+struct Sections<'a> {
+    def: &'a Def<'a>,
+    keys: &'a [&'static str],
+}
+
+impl Serialize for Sections<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let def = self.def;
+        let mut map = serializer.serialize_map(Some(self.keys.len()))?;
+        for (_, key) in SECTIONS.iter().filter(|(_, key)| self.keys.contains(key)) {
+            match *key {
+                "version" => map.serialize_entry(key, &def.version)?,
+                "divider_char" => map.serialize_entry(key, &def.divider_char)?,
+                "busbit_chars" => map.serialize_entry(key, &def.busbit_chars)?,
+                "config" => map.serialize_entry(key, &def.config)?,
+                "vias" => map.serialize_entry(key, &def.vias)?,
+                "styles" => map.serialize_entry(key, &def.styles)?,
+                "ndrs" => map.serialize_entry(key, &def.ndrs)?,
+                "regions" => map.serialize_entry(key, &def.regions)?,
+                "component_mask_shift" => map.serialize_entry(key, &def.component_mask_shift)?,
+                "components" => map.serialize_entry(key, &def.components)?,
+                "pins" => map.serialize_entry(key, &def.pins)?,
+                "pin_properties" => map.serialize_entry(key, &def.pin_properties)?,
+                "blockages" => map.serialize_entry(key, &def.blockages)?,
+                "slots" => map.serialize_entry(key, &def.slots)?,
+                "fills" => map.serialize_entry(key, &def.fills)?,
+                "special_nets" => map.serialize_entry(key, &def.special_nets)?,
+                "nets" => map.serialize_entry(key, &def.nets)?,
+                "scan_chains" => map.serialize_entry(key, &def.scan_chains)?,
+                "groups" => map.serialize_entry(key, &def.groups)?,
+                _ => map.serialize_entry(key, &def.extensions)?,
+            }
+        }
+        map.end()
+    }
+}
+
+fn run(options: &Options) -> Result<(), String> {
+    let mut data = String::new();
+    match &options.file {
+        Some(path) => {
+            data = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        }
+        None => {
+            io::stdin()
+                .read_to_string(&mut data)
+                .map_err(|e| format!("stdin: {}", e))?;
+        }
+    }
+    let name = options.file.as_deref().unwrap_or("stdin");
+    let def = parse_def(&data).map_err(|e| format!("{}: {}", name, e))?;
+
+    let all: Vec<&str> = SECTIONS.iter().map(|n| n.1).collect();
+    let json = Sections {
+        def: &def,
+        keys: options.sections.as_deref().unwrap_or(&all),
+    };
+
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    if options.pretty {
+        serde_json::to_writer_pretty(&mut out, &json)
+    } else {
+        serde_json::to_writer(&mut out, &json)
+    }
+    .map_err(|e| e.to_string())?;
+    writeln!(out)
+        .and_then(|_| out.flush())
+        .map_err(|e| e.to_string())
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.iter().any(|n| n == "-h" || n == "--help") {
+        println!("{}", USAGE);
+        return;
+    }
+    let options = match parse_args(&args) {
+        Ok(n) => n,
+        Err(e) => {
+            eprintln!("def2json: {}\n\n{}", e, USAGE);
+            process::exit(2);
+        }
+    };
+    if let Err(e) = run(&options) {
+        eprintln!("def2json: {}", e);
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(parse_args(&args(&[])).unwrap(), Options::default());
+        assert_eq!(
+            parse_args(&args(&[
+                "--sections",
+                "components,SPECIALNETS,scan_chains",
+                "--pretty",
+                "a.def"
+            ]))
+            .unwrap(),
+            Options {
+                sections: Some(vec!["components", "special_nets", "scan_chains"]),
+                pretty: true,
+                file: Some("a.def".to_string()),
+            }
+        );
+        assert_eq!(
+            parse_args(&args(&["--sections=nets", "-"]))
+                .unwrap()
+                .sections,
+            Some(vec!["nets"])
+        );
+        assert_eq!(
+            parse_args(&args(&["--sections", "nets,NETS,specialnets,special_nets"]))
+                .unwrap()
+                .sections,
+            Some(vec!["nets", "special_nets"])
+        );
+        assert!(parse_args(&args(&["--sections", "wires"]))
+            .unwrap_err()
+            .starts_with("unknown section `wires`"));
+        assert!(parse_args(&args(&["--sections"])).is_err());
+        assert!(parse_args(&args(&["a.def", "b.def"])).is_err());
+    }
+
+    #[test]
+    fn test_sections() {
+        let def = parse_def("VERSION 5.8 ;\nDESIGN test ;\nEND DESIGN\n").unwrap();
+        let json = Sections {
+            def: &def,
+            keys: &["nets", "version", "config"],
+        };
+        assert_eq!(
+            serde_json::to_string(&json).unwrap(),
+            "{\"version\":5.8,\"config\":{\"design_name\":\"test\",\"technology\":null,\
+             \"units\":null,\"history\":[],\"prop_defs\":null,\"die_area\":null,\"rows\":null,\
+             \"tracks\":null,\"gcell_grids\":null},\"nets\":[]}"
+        );
+
+        let all: Vec<&str> = SECTIONS.iter().map(|n| n.1).collect();
+        let json = Sections {
+            def: &def,
+            keys: &all,
+        };
+        assert_eq!(
+            serde_json::to_value(&json).unwrap(),
+            serde_json::to_value(&def).unwrap()
+        );
+    }
+}
@@ -1,95 +1,480 @@
 use nom::branch::alt;
 
-use nom::bytes::complete::tag;
-use nom::character::complete::{alpha1, alphanumeric1, char, digit1, multispace0, one_of};
+use nom::bytes::complete::tag as nom_tag;
+
+use nom::character::complete::{char, digit1, one_of};
 use nom::combinator::{map_res, opt, recognize};
-use nom::error::ParseError;
+use nom::error::{Error, ErrorKind, ParseError};
 use nom::multi::{many0, many1};
 
 use nom::sequence::{delimited, pair, preceded, terminated, tuple};
-use nom::IResult;
+use nom::{Err, IResult};
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::fmt;
 use std::str;
 use std::str::FromStr;
 
 // basic parse. Independent from def_parser but it's the most basic parser in def_parser.
 
+// A token or a kind of token that a parser expected but did not find.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Expected {
+    Token(&'static str), // keyword or punctuation, ie. `END NETS`, `;`
+    Kind(&'static str),  // number, name, ...
+    Once(&'static str),  // an attribute given twice, ie. `+ SOURCE`
+}
+
+impl fmt::Display for Expected {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expected::Token(n) => write!(f, "`{}`", n),
+            Expected::Kind(n) => f.write_str(n),
+            Expected::Once(n) => write!(f, "`{}` only once", n),
+        }
+    }
+}
+
+// Where the parsers are, as remaining input lengths at the start of the statement or section
+// and of the `-` member being parsed, see in_statement and in_member.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct Place {
+    pub(crate) statement: Option<usize>,
+    pub(crate) member: Option<usize>,
+}
+
+thread_local! {
+    static PLACE: Cell<Place> = const {
+        Cell::new(Place {
+            statement: None,
+            member: None,
+        })
+    };
+}
+
+// run `parse` over the statement or section starting at input
+pub(crate) fn in_statement<'a, O>(
+    input: &'a str,
+    parse: impl FnOnce(&'a str) -> IResult<&'a str, O>,
+) -> IResult<&'a str, O> {
+    let place = Place {
+        statement: Some(input.len()),
+        member: None,
+    };
+    let saved = PLACE.with(|cell| cell.replace(place));
+    let res = parse(input);
+    PLACE.with(|cell| cell.set(saved));
+    res
+}
+
+// run `parse` over the member starting at input
+pub(crate) fn in_member<'a, O>(
+    input: &'a str,
+    parse: impl FnOnce(&'a str) -> IResult<&'a str, O>,
+) -> IResult<&'a str, O> {
+    let saved = PLACE.with(|cell| {
+        cell.replace(Place {
+            member: Some(input.len()),
+            ..cell.get()
+        })
+    });
+    let res = parse(input);
+    PLACE.with(|cell| cell.set(saved));
+    res
+}
+
+// remaining input length, what was expected there and where the parsers were
+type Failure = (usize, Vec<Expected>, Place);
+
+thread_local! {
+    // Everything expected at the furthest position a parser failed, keyed by the length of
+    // the remaining input. Alternatives and optional attributes backtrack, so the furthest
+    // failure is where the input really stops matching the grammar.
+    static EXPECTED: RefCell<Failure> =
+        const { RefCell::new((usize::MAX, Vec::new(), Place { statement: None, member: None })) };
+}
+
+pub(crate) fn expect(input: &str, expected: Expected) {
+    if !RECORDING.with(Cell::get) {
+        return;
+    }
+    EXPECTED.with(|cell| {
+        let (pos, list, place) = &mut *cell.borrow_mut();
+        if input.len() < *pos {
+            *pos = input.len();
+            list.clear();
+            *place = PLACE.with(Cell::get);
+        }
+        if input.len() == *pos && !list.contains(&expected) {
+            list.push(expected);
+        }
+    })
+}
+
+// the input is wrong at this position whatever else was tried, used with Err::Failure
+pub(crate) fn reject(input: &str, expected: Expected) {
+    if RECORDING.with(Cell::get) {
+        EXPECTED
+            .with(|cell| *cell.borrow_mut() = (input.len(), vec![expected], PLACE.with(Cell::get)))
+    }
+}
+
+thread_local! {
+    // Whether expect and reject record anything. A parse runs without recording and, only
+    // when it fails, again with it to find what was expected, see recorded.
+    static RECORDING: Cell<bool> = const { Cell::new(false) };
+}
+
+// Recording of what the parsers on this thread expect, from an empty record, while it lives.
+pub(crate) struct Recording {
+    saved: bool,
+}
+
+pub(crate) fn recording() -> Recording {
+    reset_expected();
+    Recording {
+        saved: RECORDING.with(|cell| cell.replace(true)),
+    }
+}
+
+impl Drop for Recording {
+    fn drop(&mut self) {
+        let saved = self.saved;
+        RECORDING.with(|cell| cell.set(saved))
+    }
+}
+
+// Run `parse` over input and, if it fails while not recording, run it again recording what
+// was expected. The parsers have no side effects on failure, so it fails the same way.
+pub(crate) fn recorded<'a, O>(
+    input: &'a str,
+    mut parse: impl FnMut(&'a str) -> IResult<&'a str, O>,
+) -> IResult<&'a str, O> {
+    let res = parse(input);
+    if res.is_err() && !RECORDING.with(Cell::get) {
+        let _recording = recording();
+        return parse(input);
+    }
+    res
+}
+
+fn reset_expected() {
+    EXPECTED.with(|cell| *cell.borrow_mut() = (usize::MAX, Vec::new(), Place::default()))
+}
+
+// the furthest failure
+pub(crate) fn take_expected() -> Option<Failure> {
+    EXPECTED.with(|cell| {
+        let failure = cell.replace((usize::MAX, Vec::new(), Place::default()));
+        if failure.1.is_empty() {
+            None
+        } else {
+            Some(failure)
+        }
+    })
+}
+
+// remaining input length at the furthest failure, keeping the record
+pub(crate) fn peek_expected() -> Option<usize> {
+    EXPECTED.with(|cell| match &*cell.borrow() {
+        (_, list, _) if list.is_empty() => None,
+        (pos, _, _) => Some(*pos),
+    })
+}
+
+// where each skipped member went wrong and what was expected there
+type Skipped = Vec<Failure>;
+
+thread_local! {
+    // Members a parse in recovery mode skipped, None when the parse does not recover.
+    static SKIPPED: RefCell<Option<Skipped>> = const { RefCell::new(None) };
+}
+
+// Recovery of the parses on this thread while it lives. Dropping it ends the recovery,
+// also when the parse returns early or panics.
+pub(crate) struct Recovery {
+    saved: Option<Skipped>,
+}
+
+pub(crate) fn start_recovery() -> Recovery {
+    Recovery {
+        saved: SKIPPED.with(|cell| cell.replace(Some(Vec::new()))),
+    }
+}
+
+impl Recovery {
+    // the members skipped so far
+    pub(crate) fn take_skipped(&self) -> Skipped {
+        SKIPPED.with(|cell| {
+            cell.borrow_mut()
+                .as_mut()
+                .map(std::mem::take)
+                .unwrap_or_default()
+        })
+    }
+}
+
+impl Drop for Recovery {
+    fn drop(&mut self) {
+        let saved = self.saved.take();
+        SKIPPED.with(|cell| *cell.borrow_mut() = saved);
+    }
+}
+
+// When recovering, record the member at the start of input as skipped and return the input
+// after its `;`. The failure is the furthest one recorded in the member, or its start.
+pub(crate) fn skip_member(input: &str) -> Option<&str> {
+    if SKIPPED.with(|cell| cell.borrow().is_none()) {
+        return None;
+    }
+    let end = member_end(input)?;
+    let place = Place {
+        member: Some(input.len()),
+        ..PLACE.with(Cell::get)
+    };
+    let failure = match take_expected() {
+        Some((pos, expected, _)) if pos <= input.len() && pos >= input.len() - end => {
+            (pos, expected, place)
+        }
+        _ => (input.len(), vec![Expected::Kind("member")], place),
+    };
+    SKIPPED.with(|cell| cell.borrow_mut().get_or_insert_with(Vec::new).push(failure));
+    let (rest, _) = multispace_comment0::<Error<&str>>(&input[end..]).ok()?;
+    Some(rest)
+}
+
+// Length of the member at the start of input up to its closing `;`, None if there is none.
+// A `;` in a quoted string, a comment or escaped in a name does not count.
+pub(crate) fn member_end(input: &str) -> Option<usize> {
+    let bytes = input.as_bytes();
+    let mut quoted = false;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b'"' => quoted = !quoted,
+            // a comment starts where a token may, not inside a name
+            b'#' if !quoted
+                && (i == 0
+                    || matches!(
+                        bytes[i - 1],
+                        b' ' | b'\t' | b'\r' | b'\n' | b'(' | b')' | b'"'
+                    )) =>
+            {
+                i += bytes[i..].iter().position(|&c| c == b'\n')?;
+            }
+            b';' if !quoted => return Some(i + 1),
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+// run `f` with a record of its own and give this thread's record back afterwards, for a
+// parser run on a worker thread on behalf of another parse
+#[cfg(feature = "parallel")]
+pub(crate) fn isolated<T>(f: impl FnOnce() -> T) -> T {
+    let saved = EXPECTED.with(|cell| cell.replace((usize::MAX, Vec::new(), Place::default())));
+    let res = f();
+    EXPECTED.with(|cell| *cell.borrow_mut() = saved);
+    res
+}
+
+/// A combinator that records `kind` as expected when `inner` fails.
+pub(crate) fn expecting<'a, F, O>(
+    kind: &'static str,
+    mut inner: F,
+) -> impl FnMut(&'a str) -> IResult<&'a str, O>
+where
+    F: FnMut(&'a str) -> IResult<&'a str, O>,
+{
+    move |input: &'a str| {
+        let res = inner(input);
+        if res.is_err() {
+            expect(input, Expected::Kind(kind));
+        }
+        res
+    }
+}
+
+/// nom's `tag` which also records the tag as expected when it does not match.
+pub(crate) fn tag<'a>(token: &'static str) -> impl Fn(&'a str) -> IResult<&'a str, &'a str> {
+    move |input: &'a str| {
+        let res = nom::bytes::complete::tag(token)(input);
+        if res.is_err() {
+            expect(input, Expected::Token(token));
+        }
+        res
+    }
+}
+
 /// A combinator that takes a parser `inner` and produces a parser that also consumes both leading and
-/// trailing whitespace, returning the output of `inner`.
-pub fn ws<'a, F: 'a, O, E: ParseError<&'a str>>(
+/// trailing whitespace, returning the output of `inner`. Comments count as whitespace.
+pub fn ws<'a, F, O, E: ParseError<&'a str>>(
     inner: F,
 ) -> impl FnMut(&'a str) -> IResult<&'a str, O, E>
 where
-    F: FnMut(&'a str) -> IResult<&'a str, O, E>,
+    F: FnMut(&'a str) -> IResult<&'a str, O, E> + 'a,
 {
-    delimited(multispace0, inner, multispace0)
+    delimited(multispace_comment0, inner, multispace_comment0)
+}
+
+// whitespace and comments, a comment runs from `#` to the end of the line
+pub fn multispace_comment0<'a, E: ParseError<&'a str>>(
+    input: &'a str,
+) -> IResult<&'a str, &'a str, E> {
+    let mut rest = input;
+    loop {
+        rest = rest.trim_start_matches([' ', '\t', '\r', '\n']);
+        if !rest.starts_with('#') {
+            break;
+        }
+        rest = rest.find('\n').map_or("", |n| &rest[n..]);
+    }
+    Ok((rest, &input[..input.len() - rest.len()]))
 }
 
-// // typical string
-// // ie. abcdef, de234, jkl_mn, ...
+// // DEF name, ie. abcdef, de234, Z.extra1, a\/b\[0\], ...
+// Any non-whitespace characters, a backslash escapes the next character. The raw text is
+// returned, see unescape. A name may not start with a digit, + - * . or #, which start
+// numbers, points and comments. itstring also takes a digit first.
 pub fn tstring(input: &str) -> IResult<&str, &str> {
-    ws(recognize(pair(
-        alt((alpha1, tag("_"))),
-        many0(alt((alphanumeric1, tag("_")))),
-    )))(input)
+    ws(expecting("name", ident))(input)
+}
+
+// tstring without the surrounding whitespace
+fn ident(input: &str) -> IResult<&str, &str> {
+    name_token(input, false)
 }
 
 // // allow tstring preceded with number
 pub fn itstring(input: &str) -> IResult<&str, &str> {
-    ws(recognize(pair(
-        alt((alpha1, tag("_"), digit1)),
-        many0(alt((alphanumeric1, tag("_")))),
-    )))(input)
+    ws(expecting("name", |input| name_token(input, true)))(input)
+}
+
+// A name ends at whitespace or an unescaped ; ( ) ". It does not start with a character
+// that starts another token: a number, + - * . or #.
+fn name_token(input: &str, digit_first: bool) -> IResult<&str, &str> {
+    let mut chars = input.char_indices();
+    let mut end = 0;
+    while let Some((i, c)) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some((j, escaped)) => end = j + escaped.len_utf8(),
+                None => break,
+            }
+            continue;
+        }
+        let stop = c.is_whitespace()
+            || matches!(c, ';' | '(' | ')' | '"')
+            || (i == 0 && matches!(c, '+' | '-' | '*' | '.' | '#'))
+            || (i == 0 && !digit_first && c.is_ascii_digit());
+        if stop {
+            break;
+        }
+        end = i + c.len_utf8();
+    }
+    if end == 0 {
+        Err(Err::Error(Error::new(input, ErrorKind::AlphaNumeric)))
+    } else {
+        Ok((&input[end..], &input[..end]))
+    }
+}
+
+// name with its escapes removed, ie. a\/b\[0\] is a/b[0]
+pub fn unescape(name: &str) -> Cow<'_, str> {
+    if !name.contains('\\') {
+        return Cow::Borrowed(name);
+    }
+    let mut out = String::with_capacity(name.len());
+    let mut chars = name.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => out.extend(chars.next()),
+            c => out.push(c),
+        }
+    }
+    Cow::Owned(out)
 }
 
 // // parse string that is surrounded by " and ".
-// // ie, "abc", "def"
+// // ie, "abc", "placed by tool v2.1", "say \"hi\""
+// The content between the quotes is returned as is, escapes included, see unescape.
 pub fn qstring(input: &str) -> IResult<&str, &str> {
-    ws(recognize(delimited(tag("\""), tstring, tag("\""))))(input)
+    ws(expecting("quoted string", quoted))(input)
+}
+
+fn quoted(input: &str) -> IResult<&str, &str> {
+    let content = match input.strip_prefix('"') {
+        Some(n) => n,
+        None => return Err(Err::Error(Error::new(input, ErrorKind::Char))),
+    };
+    let mut chars = content.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '"' => return Ok((&content[i + 1..], &content[..i])),
+            _ => {}
+        }
+    }
+    // no closing quote
+    Err(Err::Error(Error::new(input, ErrorKind::Char)))
 }
 
-// use for component pattern recognize
+// component name pattern of a group, a name in which * matches any characters, or only *
 pub fn component_pattern(input: &str) -> IResult<&str, &str> {
-    ws(recognize(tuple((
-        alt((alpha1, tag("_"))),
-        many0(alt((alphanumeric1, tag("_")))),
-        opt(tag("*")),
-    ))))(input)
+    ws(expecting(
+        "name",
+        alt((recognize(pair(ident, opt(nom_tag("*")))), nom_tag("*"))),
+    ))(input)
 }
 
 // // signed integer number
 // // ie, 100, -20
 pub fn number(input: &str) -> IResult<&str, i32> {
-    ws(map_res(
-        recognize(pair(opt(alt((tag("+"), tag("-")))), digit1)),
-        |res: &str| i32::from_str(res),
+    ws(expecting(
+        "number",
+        map_res(
+            recognize(pair(opt(alt((nom_tag("+"), nom_tag("-")))), digit1)),
+            |res: &str| i32::from_str(res),
+        ),
     ))(input)
 }
 
 pub fn number_str(input: &str) -> IResult<&str, &str> {
-    ws(recognize(pair(opt(alt((tag("+"), tag("-")))), digit1)))(input)
+    ws(expecting(
+        "number",
+        recognize(pair(opt(alt((nom_tag("+"), nom_tag("-")))), digit1)),
+    ))(input)
 }
 
 // parse unsigned floating number
 // The following is adapted from the Python parser by Valentin Lorentz (ProgVal).
 pub fn float(input: &str) -> IResult<&str, f64> {
-    ws(map_res(
-        alt((
-            // Case one: .42
-            recognize(tuple((
-                char('.'),
-                decimal,
-                opt(tuple((one_of("eE"), opt(one_of("+-")), decimal))),
-            ))), // Case two: 42e42 and 42.42e42
-            recognize(tuple((
-                decimal,
-                opt(preceded(char('.'), decimal)),
-                one_of("eE"),
-                opt(one_of("+-")),
-                decimal,
-            ))), // Case three: 42. and 42.42
-            recognize(tuple((decimal, char('.'), opt(decimal)))),
-        )),
-        |res: &str| f64::from_str(res),
+    ws(expecting(
+        "real number",
+        map_res(
+            alt((
+                // Case one: .42
+                recognize(tuple((
+                    char('.'),
+                    decimal,
+                    opt(tuple((one_of("eE"), opt(one_of("+-")), decimal))),
+                ))), // Case two: 42e42 and 42.42e42
+                recognize(tuple((
+                    decimal,
+                    opt(preceded(char('.'), decimal)),
+                    one_of("eE"),
+                    opt(one_of("+-")),
+                    decimal,
+                ))), // Case three: 42. and 42.42
+                recognize(tuple((decimal, char('.'), opt(decimal)))),
+            )),
+            |res: &str| f64::from_str(res),
+        ),
     ))(input)
 }
 
@@ -102,6 +487,7 @@ mod tests {
     use crate::collection::base::*;
 
     #[test]
+    #[allow(clippy::approx_constant)]
     fn test_float() {
         assert_eq!(float("3.14").unwrap(), ("", 3.14));
         assert_eq!(float(" 3.14").unwrap(), ("", 3.14));
@@ -113,9 +499,61 @@ mod tests {
         assert_eq!(tstring("ab23cd_edf ").unwrap(), ("", "ab23cd_edf"));
         assert_eq!(tstring(" ab23cd_edf").unwrap(), ("", "ab23cd_edf"));
     }
+    #[test]
+    fn test_escaped_tstring() {
+        assert_eq!(tstring("Z.extra1 ;").unwrap(), (";", "Z.extra1"));
+        assert_eq!(tstring("a$b-c!d )").unwrap(), (")", "a$b-c!d"));
+        assert_eq!(tstring("\\[x\\]\\ y z").unwrap(), ("z", "\\[x\\]\\ y"));
+        assert_eq!(tstring("u1/u2\\/n[0];").unwrap(), (";", "u1/u2\\/n[0]"));
+        assert!(tstring("1abc").is_err());
+        assert!(tstring("-abc").is_err());
+        assert!(tstring("+abc").is_err());
+        assert!(tstring(".abc").is_err());
+        assert!(tstring("*abc").is_err());
+        assert_eq!(itstring("2W ;").unwrap(), (";", "2W"));
+        assert_eq!(unescape("u1/u2\\/n\\[0\\]"), "u1/u2/n[0]");
+        assert_eq!(unescape("plain"), Cow::Borrowed("plain"));
+    }
+
+    #[test]
+    fn test_component_pattern() {
+        assert_eq!(component_pattern("top/u1 ;").unwrap(), (";", "top/u1"));
+        assert_eq!(component_pattern("a\\[3\\] ;").unwrap(), (";", "a\\[3\\]"));
+        assert_eq!(component_pattern("Z.extra1 ;").unwrap(), (";", "Z.extra1"));
+        assert_eq!(component_pattern("u1/* ;").unwrap(), (";", "u1/*"));
+        assert_eq!(component_pattern("* ;").unwrap(), (";", "*"));
+        assert!(component_pattern("; ").is_err());
+    }
+
     #[test]
     fn test_qstring() {
-        assert_eq!(qstring("\"abcd_edf\"").unwrap(), ("", "\"abcd_edf\""));
+        assert_eq!(qstring("\"abcd_edf\"").unwrap(), ("", "abcd_edf"));
+        assert_eq!(
+            qstring(" \"placed by tool v2.1\" ;").unwrap(),
+            (";", "placed by tool v2.1")
+        );
+        assert_eq!(
+            qstring("\"say \\\"hi\\\"\"").unwrap(),
+            ("", "say \\\"hi\\\"")
+        );
+        assert_eq!(unescape("say \\\"hi\\\""), "say \"hi\"");
+        assert!(qstring("\"open").is_err());
+    }
+
+    #[test]
+    fn test_comment() {
+        assert_eq!(tstring("abc # comment\n def").unwrap(), ("def", "abc"));
+        assert_eq!(number("# line 1\n  # line 2\n 12 # end").unwrap(), ("", 12));
+        assert_eq!(qstring("\"abc # x\"").unwrap(), ("", "abc # x"));
+    }
+
+    #[test]
+    fn test_member_end() {
+        assert_eq!(member_end("- I1 A ; - I2 B ;"), Some(8));
+        assert_eq!(member_end("- I1 A + PROPERTY note \"a ; b\" ;"), Some(32));
+        assert_eq!(member_end("- I1 A # a ; comment\n ;"), Some(23));
+        assert_eq!(member_end("- a\\;b#c A ;"), Some(12));
+        assert_eq!(member_end("- I1 A"), None);
     }
 
     #[test]
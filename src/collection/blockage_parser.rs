@@ -1,14 +1,15 @@
+use std::borrow::Cow;
+
 // nom
 use nom::branch::alt;
-use nom::bytes::complete::tag;
 use nom::combinator::{map, opt};
 use nom::multi::many0;
 use nom::sequence::{delimited, preceded, terminated, tuple};
 use nom::IResult;
 // def
-use super::base::{float, number, tstring, ws};
-use super::common::rect_or_polygon;
-use super::def_types::Blockage;
+use super::base::{float, number, tag, tstring, ws};
+use super::common::{members, rect_or_polygon};
+use super::def_types::{Blockage, LayerRule};
 
 pub fn blockage_section(
     input: &str,
@@ -16,20 +17,20 @@ pub fn blockage_section(
     &str,
     (
         i32, // numBlockage
-        Vec<Blockage>,
+        Vec<Blockage<'_>>,
     ),
 > {
     delimited(
         tag("BLOCKAGES"),
         tuple((
             terminated(number, ws(tag(";"))), // blockage num
-            many0(blockage_member),
+            members(blockage_member),
         )),
         tag("END BLOCKAGES"),
     )(input)
 }
 
-fn blockage_member(input: &str) -> IResult<&str, Blockage> {
+pub(crate) fn blockage_member(input: &str) -> IResult<&str, Blockage<'_>> {
     delimited(
         tag("-"),
         alt((
@@ -39,20 +40,37 @@ fn blockage_member(input: &str) -> IResult<&str, Blockage> {
                     layer_blockage_rule,
                     many0(rect_or_polygon),
                 )),
-                |res| Blockage::Layer(res),
+                |n| Blockage::Layer {
+                    layer: n.0.into(),
+                    slots: (n.1).0,
+                    pushdown: (n.1).1,
+                    fills: (n.1).2,
+                    except_pg_net: (n.1).3,
+                    rule: (n.1).4,
+                    component: (n.1).5.map(Cow::from),
+                    mask: (n.1).6,
+                    shapes: n.2,
+                },
             ),
             map(
                 tuple((
                     preceded(ws(tag("PLACEMENT")), placement_blockage_rule),
                     many0(rect_or_polygon),
                 )),
-                |res| Blockage::Placement(res),
+                |n| Blockage::Placement {
+                    soft: (n.0).0,
+                    partial: (n.0).1,
+                    pushdown: (n.0).2,
+                    component: (n.0).3.map(Cow::from),
+                    shapes: n.1,
+                },
             ),
         )),
         ws(tag(";")),
     )(input)
 }
 
+#[allow(clippy::type_complexity)]
 fn layer_blockage_rule(
     input: &str,
 ) -> IResult<
@@ -62,38 +80,33 @@ fn layer_blockage_rule(
         bool, // PUSHDOWN. Specifies that whether the blockage was pushed down into the block from the top level of the design.
         bool, // FILLS. Whether creates a blockage on the specified layer where metal fills cannot be placed.
         bool, // EXCEPTPGNET. Indicates that whether the blockage only blocks signal net routing, and does not block power or ground net routing.
-        Option<i32>, // SPACING or DESIGNRULEWIDTH. minimum spacing allowed between the blockage and any other routing shape
-        Option<&str>, // COMPONENT. component with which to associate a blockage.
+        Option<LayerRule>, // SPACING or DESIGNRULEWIDTH. minimum spacing allowed between the blockage and any other routing shape
+        Option<&str>,      // COMPONENT. component with which to associate a blockage.
+        Option<i32>,       // MASK. mask number of the blockage shapes
     ),
 > {
     tuple((
-        map(opt(ws(tag("+ SLOTS"))), |res: Option<&str>| match res {
-            Some(_) => true,
-            None => false,
-        }),
-        map(opt(ws(tag("+ PUSHDOWN"))), |res: Option<&str>| match res {
-            Some(_) => true,
-            None => false,
+        map(opt(ws(tag("+ SLOTS"))), |res: Option<&str>| res.is_some()),
+        map(opt(ws(tag("+ PUSHDOWN"))), |res: Option<&str>| {
+            res.is_some()
         }),
-        map(opt(ws(tag("+ FILLS"))), |res: Option<&str>| match res {
-            Some(_) => true,
-            None => false,
+        map(opt(ws(tag("+ FILLS"))), |res: Option<&str>| res.is_some()),
+        map(opt(ws(tag("+ EXCEPTPGNET"))), |res: Option<&str>| {
+            res.is_some()
         }),
-        map(
-            opt(ws(tag("+ EXCEPTPGNET"))),
-            |res: Option<&str>| match res {
-                Some(_) => true,
-                None => false,
-            },
-        ),
         opt(alt((
-            preceded(ws(tag("+ SPACING")), number),
-            preceded(ws(tag("+ DESIGNRULEWIDTH")), number),
+            map(preceded(ws(tag("+ SPACING")), number), LayerRule::Spacing),
+            map(
+                preceded(ws(tag("+ DESIGNRULEWIDTH")), number),
+                LayerRule::DesignRuleWidth,
+            ),
         ))),
         opt(preceded(ws(tag("+ COMPONENT")), tstring)),
+        opt(preceded(ws(tag("+ MASK")), number)),
     ))(input)
 }
 
+#[allow(clippy::type_complexity)]
 fn placement_blockage_rule(
     input: &str,
 ) -> IResult<
@@ -106,92 +119,9 @@ fn placement_blockage_rule(
     ),
 > {
     tuple((
-        map(opt(ws(tag("+ SOFT"))), |res: Option<&str>| match res {
-            Some(_) => true,
-            None => false,
-        }),
+        map(opt(ws(tag("+ SOFT"))), |res: Option<&str>| res.is_some()),
         opt(preceded(ws(tag("+ PARTIAL")), float)),
-        map(opt(tag("+ PUSHDOWN")), |res: Option<&str>| match res {
-            Some(_) => true,
-            None => false,
-        }),
+        map(opt(tag("+ PUSHDOWN")), |res: Option<&str>| res.is_some()),
         opt(preceded(ws(tag("+ COMPONENT")), tstring)),
     ))(input)
 }
-
-// #[cfg(test)]
-// mod tests {
-//     use super::blockage_parser::*;
-//     use super::def_types::*;
-//     use std::io::Read;
-
-//     #[test]
-//     fn test_blockage_section() {
-//         let mut input_def = std::fs::File::open("tests/blockage_test.def").unwrap();
-//         let mut data = String::new();
-//         input_def.read_to_string(&mut data).unwrap();
-//         let result = blockage_section(&data).unwrap();
-
-//         let blockage_section = result.1;
-
-//         let num = blockage_section.0;
-//         let blockages = blockage_section.1;
-
-//         assert_eq!(num, 8);
-//         assert_eq!(
-//             blockages,
-//             vec![
-//                 Blockage::Layer((
-//                     "METAL1",
-//                     (false, false, false, false, None, None, Some(1)),
-//                     vec![Geometry::Rect(((60, 70), (80, 90)))]
-//                 )),
-//                 Blockage::Layer((
-//                     "M2",
-//                     (true, true, false, true, None, Some("I1"), Some(3)),
-//                     vec![Geometry::Polygon(vec![
-//                         (100, 100),
-//                         (100, 200),
-//                         (150, 200),
-//                         (150, 150),
-//                         (200, 150),
-//                         (200, 100)
-//                     ])]
-//                 )),
-//                 Blockage::Layer((
-//                     "M2",
-//                     (true, false, false, false, None, None, Some(2)),
-//                     vec![Geometry::Rect(((10, 20), (40, 50)))]
-//                 )),
-//                 Blockage::Layer((
-//                     "M1",
-//                     (false, true, false, false, Some(3), None, Some(1)),
-//                     vec![Geometry::Rect(((50, 30), (55, 40)))]
-//                 )),
-//                 Blockage::Layer((
-//                     "M1",
-//                     (false, false, false, true, Some(45), None, Some(1)),
-//                     vec![Geometry::Rect(((50, 30), (55, 40)))]
-//                 )),
-//                 Blockage::Placement((
-//                     (false, None, false, None),
-//                     vec![
-//                         Geometry::Rect(((-15, 0), (0, 20))),
-//                         Geometry::Rect(((-10, -15), (50, 0)))
-//                     ]
-//                 )),
-//                 Blockage::Placement((
-//                     (false, Some(0.4), true, Some("I1")),
-//                     vec![
-//                         Geometry::Rect(((-10, 0), (0, 20))),
-//                         Geometry::Rect(((-10, -5), (50, 0)))
-//                     ]
-//                 )),
-//                 Blockage::Placement((
-//                     (true, None, false, None),
-//                     vec![Geometry::Rect(((50, 30), (55, 40)))]
-//                 )),
-//             ]
-//         );
-//     }
-// }
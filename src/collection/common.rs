@@ -1,17 +1,23 @@
 use nom::branch::alt;
 
-use nom::bytes::complete::tag;
 use nom::character::complete::{char, space0};
-use nom::combinator::{map, opt, recognize};
+use nom::combinator::{map, opt, verify};
 
 use nom::multi::{many0, many1};
 
-use nom::sequence::{delimited, pair, preceded, separated_pair, tuple};
-use nom::IResult;
+use nom::error::{Error, ErrorKind};
+use nom::sequence::{delimited, preceded, separated_pair, tuple};
+use nom::{Err, IResult};
+use std::borrow::Cow;
 use std::str;
 
-use super::base::{float, number, number_str, qstring, tstring, ws};
-use super::def_types::{Geometry, PropValue, Properties, RouteBody, RouteElem, RtPt};
+use super::base::{
+    float, in_member, number, number_str, qstring, reject, skip_member, tag, tstring, ws, Expected,
+};
+use super::def_types::{
+    Geometry, NetConnection, PropValue, Properties, Rect, RouteBody, RouteElem, RtPt,
+};
+use super::encoder::orient_encode;
 // common parser used in def_parser. These parser are very commonly used in def_parser so collect them together.
 
 pub fn pt(input: &str) -> IResult<&str, (&str, &str)> {
@@ -35,19 +41,19 @@ pub fn pt_new(input: &str) -> IResult<&str, (i32, i32)> {
 }
 
 // routing pt
-pub fn rtpt(input: &str) -> IResult<&str, RtPt> {
+pub fn rtpt(input: &str) -> IResult<&str, RtPt<'_>> {
     delimited(
         ws(tag("(")),
         tuple((
-            alt((map(ws(tag("*")), |_| None), map(number, |n| Some(n)))),
-            alt((map(ws(tag("*")), |_| None), map(number, |n| Some(n)))),
+            alt((map(ws(tag("*")), |_| None), map(number, Some))),
+            alt((map(ws(tag("*")), |_| None), map(number, Some))),
             opt(number),
         )),
         ws(tag(")")),
     )(input)
 }
 
-pub fn rect(input: &str) -> IResult<&str, ((i32, i32), (i32, i32))> {
+pub fn rect(input: &str) -> IResult<&str, Rect> {
     tuple((
         delimited(
             ws(tag("(")),
@@ -99,37 +105,113 @@ pub fn x_or_y(input: &str) -> IResult<&str, char> {
 //     alt((tag("ON"), tag("OFF")))(input)
 // }
 
-// pub fn inline_comment(input: &str) -> IResult<&str, &str> {
-//     delimited(tag("#"), is_not("\n"), tag("\n"))(input)
-// }
-
-// different from tstring and qstring, comp_name might contain hierachical struct and bus bit information
-// ie, i1/i2[2]/i3.
-// Only support use busbit_char = "[]", divider char = "/"
-pub fn comp_name(input: &str) -> IResult<&str, &str> {
-    ws(recognize(pair(
-        tstring,
-        many0(alt((
-            recognize(many1(tuple((tag("["), number, tag("]"))))),
-            recognize(pair(tag("/"), tstring)),
-        ))),
-    )))(input)
+// ( compName pinName [+ SYNTHESIZED] ) used in NET and SPECIALNET
+pub fn net_connection(input: &str) -> IResult<&str, NetConnection<'_>> {
+    map(
+        delimited(
+            ws(tag("(")),
+            tuple((
+                alt((tstring, ws(tag("*")))),
+                tstring,
+                map(opt(ws(tag("+ SYNTHESIZED"))), |res: Option<&str>| {
+                    res.is_some()
+                }),
+            )),
+            ws(tag(")")),
+        ),
+        |n| NetConnection {
+            component: match n.0 {
+                "PIN" => None,
+                c => Some(c.into()),
+            },
+            pin: n.1.into(),
+            synthesized: n.2,
+        },
+    )(input)
 }
 
 // Properties
-pub fn properties(input: &str) -> IResult<&str, Properties> {
-    many0(preceded(
+pub fn properties(input: &str) -> IResult<&str, Properties<'_>> {
+    many0(property)(input)
+}
+
+pub fn property(input: &str) -> IResult<&str, (Cow<'_, str>, PropValue<'_>)> {
+    preceded(
         ws(tag("+ PROPERTY")),
         tuple((
-            tstring,
+            map(tstring, Cow::from),
             alt((
-                map(tstring, |res: &str| PropValue::SValue(res)),
-                map(qstring, |res: &str| PropValue::SValue(res)),
+                map(tstring, |res: &str| PropValue::SValue(res.into())),
+                map(qstring, |res: &str| PropValue::SValue(res.into())),
                 map(float, |res: f64| PropValue::RValue(res)),
                 map(number, |res: i32| PropValue::IValue(res)),
             )),
         )),
-    ))(input)
+    )(input)
+}
+
+// `+` attributes of a statement in any order. `once` gives the keyword of an attribute that
+// may appear only once, and None for repeatable ones. A second one fails at its position.
+pub fn attributes<'a, A, P, K>(
+    mut attribute: P,
+    once: K,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<A>>
+where
+    P: FnMut(&'a str) -> IResult<&'a str, A>,
+    K: Fn(&A) -> Option<&'static str>,
+{
+    move |mut input: &'a str| {
+        let mut out = Vec::new();
+        let mut seen = Vec::new();
+        loop {
+            match attribute(input) {
+                Ok((rest, attr)) => {
+                    if let Some(keyword) = once(&attr) {
+                        if seen.contains(&keyword) {
+                            let at = input.trim_start();
+                            reject(at, Expected::Once(keyword));
+                            return Err(Err::Failure(Error::new(at, ErrorKind::Verify)));
+                        }
+                        seen.push(keyword);
+                    }
+                    out.push(attr);
+                    input = rest;
+                }
+                Err(Err::Error(_)) => return Ok((input, out)),
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+// `- ... ;` members of a section, many0 of `member`. A parse that recovers from errors skips a
+// member that fails to its `;` and goes on with the next one, see skip_member.
+pub fn members<'a, T, P>(mut member: P) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<T>>
+where
+    P: FnMut(&'a str) -> IResult<&'a str, T>,
+{
+    move |mut input: &'a str| {
+        let mut out = Vec::new();
+        loop {
+            match in_member(input, &mut member) {
+                Ok((rest, n)) => {
+                    out.push(n);
+                    input = rest;
+                }
+                Err(e) => {
+                    let skipped = match &e {
+                        Err::Error(_) if !input.starts_with('-') => None,
+                        _ => skip_member(input),
+                    };
+                    match (skipped, e) {
+                        (Some(rest), _) => input = rest,
+                        (None, Err::Error(_)) => return Ok((input, out)),
+                        (None, e) => return Err(e),
+                    }
+                }
+            }
+        }
+    }
 }
 
 pub fn rect_or_polygon(input: &str) -> IResult<&str, Geometry> {
@@ -144,17 +226,57 @@ pub fn rect_or_polygon(input: &str) -> IResult<&str, Geometry> {
     ))(input)
 }
 
-// Routing point
-pub fn route_body(input: &str) -> IResult<&str, RouteBody> {
-    many0(alt((
-        map(pair(rtpt, tstring), |res| RouteElem::Via(res)),
-        map(rtpt, |res| RouteElem::Pt(res)),
-    )))(input)
+// Routing point. A name after a point or a via is a via, except NEW which starts the next wire
+// and the keywords of the route elements. MASK before a point is the mask of the wire to it and
+// before a name the mask of the via. A via may be followed by its orient and a DO BY STEP array.
+pub fn route_body(input: &str) -> IResult<&str, RouteBody<'_>> {
+    many0(route_elem)(input)
+}
+
+pub fn route_elem(input: &str) -> IResult<&str, RouteElem<'_>> {
+    alt((
+        map(tuple((rtpt, via)), |(pt, mut n)| {
+            if let RouteElem::Via { point, .. } = &mut n {
+                *point = Some(pt);
+            }
+            n
+        }),
+        map(rtpt, RouteElem::Pt),
+        via,
+        map(preceded(ws(tag("MASK")), number), RouteElem::Mask),
+    ))(input)
+}
+
+// [MASK viaMaskNum] viaName [orient] [DO numX BY numY STEP stepX stepY], without its point
+fn via(input: &str) -> IResult<&str, RouteElem<'_>> {
+    map(
+        tuple((
+            opt(preceded(ws(tag("MASK")), number)),
+            verify(tstring, |n: &str| {
+                !["NEW", "MASK", "RECT", "VIRTUAL"].contains(&n)
+            }),
+            opt(orient_encode),
+            opt(tuple((
+                preceded(ws(tag("DO")), number),
+                preceded(ws(tag("BY")), number),
+                preceded(ws(tag("STEP")), number),
+                number,
+            ))),
+        )),
+        |n| RouteElem::Via {
+            point: None,
+            mask: n.0,
+            via: n.1.into(),
+            orient: n.2,
+            array: n.3,
+        },
+    )(input)
 }
 
 #[cfg(test)]
 mod tests {
     use crate::collection::common::*;
+    use crate::collection::def_types::Orient;
 
     #[test]
     fn test_pt() {
@@ -185,11 +307,11 @@ mod tests {
             (
                 "",
                 vec![
-                    ("strprop", PropValue::SValue("\"aString\"")),
-                    ("intprop", PropValue::IValue(1)),
-                    ("realprop", PropValue::RValue(1.1)),
-                    ("intrangeprop", PropValue::IValue(25)),
-                    ("realrangeprop", PropValue::RValue(25.25))
+                    ("strprop".into(), PropValue::SValue("aString".into())),
+                    ("intprop".into(), PropValue::IValue(1)),
+                    ("realprop".into(), PropValue::RValue(1.1)),
+                    ("intrangeprop".into(), PropValue::IValue(25)),
+                    ("realrangeprop".into(), PropValue::RValue(25.25))
                 ]
             )
         );
@@ -205,8 +327,71 @@ mod tests {
                 vec![
                     RouteElem::Pt((Some(14000), Some(341440), None)),
                     RouteElem::Pt((Some(9600), None, None)),
-                    RouteElem::Via(((None, Some(282400), None), "M1_M2")),
-                    RouteElem::Via(((Some(2400), None, None), "VIAGEN12_0")),
+                    RouteElem::Via {
+                        point: Some((None, Some(282400), None)),
+                        mask: None,
+                        via: "M1_M2".into(),
+                        orient: None,
+                        array: None,
+                    },
+                    RouteElem::Via {
+                        point: Some((Some(2400), None, None)),
+                        mask: None,
+                        via: "VIAGEN12_0".into(),
+                        orient: None,
+                        array: None,
+                    },
+                ]
+            )
+        );
+        assert_eq!(
+            route_body("( 2400 282400 ) ( 240 * ) NEW M1").unwrap(),
+            (
+                "NEW M1",
+                vec![
+                    RouteElem::Pt((Some(2400), Some(282400), None)),
+                    RouteElem::Pt((Some(240), None, None)),
+                ]
+            )
+        );
+        assert_eq!(
+            route_body("( 10 10 ) MASK 1 ( 20 * ) MASK 031 VIAGEN12_3 MASK 2 ( * 30 ) ;").unwrap(),
+            (
+                ";",
+                vec![
+                    RouteElem::Pt((Some(10), Some(10), None)),
+                    RouteElem::Mask(1),
+                    RouteElem::Via {
+                        point: Some((Some(20), None, None)),
+                        mask: Some(31),
+                        via: "VIAGEN12_3".into(),
+                        orient: None,
+                        array: None,
+                    },
+                    RouteElem::Mask(2),
+                    RouteElem::Pt((None, Some(30), None)),
+                ]
+            )
+        );
+        assert_eq!(
+            route_body("( 0 0 ) M1_M2 N ( 10 0 ) M1_M2 FN DO 2 BY 3 STEP 200 100 NEW").unwrap(),
+            (
+                "NEW",
+                vec![
+                    RouteElem::Via {
+                        point: Some((Some(0), Some(0), None)),
+                        mask: None,
+                        via: "M1_M2".into(),
+                        orient: Some(Orient::N),
+                        array: None,
+                    },
+                    RouteElem::Via {
+                        point: Some((Some(10), Some(0), None)),
+                        mask: None,
+                        via: "M1_M2".into(),
+                        orient: Some(Orient::FN),
+                        array: Some((2, 3, 200, 100)),
+                    },
                 ]
             )
         );
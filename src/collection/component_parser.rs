@@ -1,15 +1,17 @@
+use std::borrow::Cow;
+
 // nom
 
-use nom::bytes::complete::tag;
+use nom::branch::alt;
 use nom::combinator::{map, opt};
-use nom::multi::many0;
-use nom::sequence::{delimited, pair, preceded, terminated, tuple};
-use nom::IResult;
+use nom::error::{Error, ErrorKind};
+use nom::sequence::{delimited, preceded, terminated, tuple};
+use nom::{Err, IResult};
 
 // def
-use super::base::{number, tstring, ws};
-use super::common::{comp_name, properties, pt_new};
-use super::def_types::Component;
+use super::base::{expect, number, tag, tstring, ws, Expected};
+use super::common::{attributes, members, property, pt_new};
+use super::def_types::{Component, Halo, Placement, PropValue, RouteHalo, Source};
 use super::encoder::{component_location_attribute_encode, orient_encode, source_type_encode};
 
 pub fn component_section(
@@ -18,54 +20,155 @@ pub fn component_section(
     &str,
     (
         i32, // number of Component
-        Vec<Component>,
+        Vec<Component<'_>>,
     ),
 > {
     delimited(
         tag("COMPONENTS"),
         tuple((
             terminated(number, ws(tag(";"))), // numComponent
-            many0(component_member),
+            members(component_member),
         )),
         ws(tag("END COMPONENTS")),
     )(input)
 }
 
-fn component_member(input: &str) -> IResult<&str, Component> {
-    delimited(
-        tag("-"),
-        pair(
-            tuple((comp_name, tstring)),
+// `+` attributes of a component, in any order
+enum ComponentAttribute<'a> {
+    EeqMaster(Cow<'a, str>),
+    Source(Source),
+    Placement(Placement),
+    Weight(i32),
+    Region(Cow<'a, str>),
+    Halo(Halo),
+    RouteHalo(RouteHalo<'a>),
+    Property((Cow<'a, str>, PropValue<'a>)),
+}
+
+impl ComponentAttribute<'_> {
+    // keyword of an attribute given at most once
+    fn once(&self) -> Option<&'static str> {
+        match self {
+            ComponentAttribute::EeqMaster(_) => Some("+ EEQMASTER"),
+            ComponentAttribute::Source(_) => Some("+ SOURCE"),
+            ComponentAttribute::Placement(_) => Some("placement status"),
+            ComponentAttribute::Weight(_) => Some("+ WEIGHT"),
+            ComponentAttribute::Region(_) => Some("+ REGION"),
+            ComponentAttribute::Halo(_) => Some("+ HALO"),
+            ComponentAttribute::RouteHalo(_) => Some("+ ROUTEHALO"),
+            ComponentAttribute::Property(_) => None,
+        }
+    }
+}
+
+fn component_attribute(input: &str) -> IResult<&str, ComponentAttribute<'_>> {
+    alt((
+        map(preceded(ws(tag("+ EEQMASTER")), tstring), |n| {
+            ComponentAttribute::EeqMaster(n.into())
+        }),
+        map(source_type_encode, ComponentAttribute::Source),
+        map(
             tuple((
-                opt(preceded(ws(tag("+ EEQMASTER")), tstring)),
-                opt(source_type_encode),
-                tuple((
-                    component_location_attribute_encode,
-                    opt(tuple((pt_new, orient_encode))),
-                )),
-                opt(preceded(ws(tag("+ WEIGHT")), number)),
-                opt(preceded(ws(tag("+ REGION")), tstring)),
-                opt(tuple((
-                    map(
-                        preceded(ws(tag("+ HALO")), opt(ws(tag("SOFT")))),
-                        |res: Option<&str>| match res {
-                            Some(_) => true,
-                            None => false,
-                        },
-                    ),
-                    number,
-                    number,
-                    number,
-                    number,
-                ))),
-                opt(tuple((
-                    preceded(ws(tag("+ ROUTEHALO")), number),
-                    tstring,
-                    tstring,
-                ))),
-                properties,
+                component_location_attribute_encode,
+                opt(tuple((pt_new, orient_encode))),
             )),
+            |n| {
+                ComponentAttribute::Placement(Placement {
+                    status: n.0,
+                    location: n.1,
+                })
+            },
         ),
-        ws(tag(";")),
-    )(input)
+        map(
+            preceded(ws(tag("+ WEIGHT")), number),
+            ComponentAttribute::Weight,
+        ),
+        map(preceded(ws(tag("+ REGION")), tstring), |n| {
+            ComponentAttribute::Region(n.into())
+        }),
+        map(
+            tuple((
+                map(
+                    preceded(ws(tag("+ HALO")), opt(ws(tag("SOFT")))),
+                    |res: Option<&str>| res.is_some(),
+                ),
+                number,
+                number,
+                number,
+                number,
+            )),
+            |n| {
+                ComponentAttribute::Halo(Halo {
+                    soft: n.0,
+                    left: n.1,
+                    bottom: n.2,
+                    right: n.3,
+                    top: n.4,
+                })
+            },
+        ),
+        map(
+            tuple((preceded(ws(tag("+ ROUTEHALO")), number), tstring, tstring)),
+            |n| {
+                ComponentAttribute::RouteHalo(RouteHalo {
+                    dist: n.0,
+                    min_layer: n.1.into(),
+                    max_layer: n.2.into(),
+                })
+            },
+        ),
+        map(property, ComponentAttribute::Property),
+    ))(input)
+}
+
+pub(crate) fn component_member(input: &str) -> IResult<&str, Component<'_>> {
+    let (input, (name, model)) = preceded(tag("-"), tuple((tstring, tstring)))(input)?;
+    let (rest, attrs) = attributes(component_attribute, ComponentAttribute::once)(input)?;
+
+    let mut eeqmaster = None;
+    let mut source = None;
+    let mut placement = None;
+    let mut weight = None;
+    let mut region = None;
+    let mut halo = None;
+    let mut route_halo = None;
+    let mut properties = Vec::new();
+    for attr in attrs {
+        match attr {
+            ComponentAttribute::EeqMaster(n) => eeqmaster = Some(n),
+            ComponentAttribute::Source(n) => source = Some(n),
+            ComponentAttribute::Placement(n) => placement = Some(n),
+            ComponentAttribute::Weight(n) => weight = Some(n),
+            ComponentAttribute::Region(n) => region = Some(n),
+            ComponentAttribute::Halo(n) => halo = Some(n),
+            ComponentAttribute::RouteHalo(n) => route_halo = Some(n),
+            ComponentAttribute::Property(n) => properties.push(n),
+        }
+    }
+    // the placement status is required
+    let placement = match placement {
+        Some(n) => n,
+        None => {
+            let at = rest.trim_start();
+            expect(at, Expected::Kind("placement status"));
+            return Err(Err::Failure(Error::new(at, ErrorKind::Verify)));
+        }
+    };
+
+    let (rest, _) = ws(tag(";"))(rest)?;
+    Ok((
+        rest,
+        Component {
+            name: name.into(),
+            model: model.into(),
+            eeqmaster,
+            source,
+            placement,
+            weight,
+            region,
+            halo,
+            route_halo,
+            properties,
+        },
+    ))
 }
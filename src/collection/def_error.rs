@@ -0,0 +1,374 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+// nom
+use nom::error::Error as NomError;
+use nom::Err;
+
+// def
+use super::base::{take_expected, Expected, Place};
+
+// keywords starting a statement or a section, the section an error is in is one of them
+const STATEMENTS: &[&str] = &[
+    "VERSION",
+    "DIVIDERCHAR",
+    "BUSBITCHARS",
+    "DESIGN",
+    "TECHNOLOGY",
+    "UNITS",
+    "HISTORY",
+    "PROPERTYDEFINITIONS",
+    "DIEAREA",
+    "ROW",
+    "TRACKS",
+    "GCELLGRID",
+    "VIAS",
+    "STYLES",
+    "NONDEFAULTRULES",
+    "REGIONS",
+    "COMPONENTMASKSHIFT",
+    "COMPONENTS",
+    "PINS",
+    "PINPROPERTIES",
+    "BLOCKAGES",
+    "SLOTS",
+    "FILLS",
+    "SPECIALNETS",
+    "NETS",
+    "SCANCHAINS",
+    "GROUPS",
+    "BEGINEXT",
+];
+
+// byte offset of an error, what was expected there and the statement and member it is in
+pub(crate) type Located = (usize, Vec<String>, Place);
+
+// Error of parse_def, pointing at where the input stops matching the DEF syntax. It is
+// returned once per parse, so it is not boxed for clippy::result_large_err.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DefError {
+    pub line: usize,             // 1-based line of the failure
+    pub column: usize,           // 1-based column in characters
+    pub section: Option<String>, // statement or section keyword, ie. NETS
+    pub member: Option<String>,  // name after "-" of the member being parsed
+    pub expected: Vec<String>,   // tokens that would have matched at this point
+    pub found: Option<String>,   // what was there instead, when it is worth saying
+    pub snippet: String,         // the source line of the failure
+}
+
+impl DefError {
+    // `offset` is the byte offset of the failure in `source`
+    pub(crate) fn new(source: &str, offset: usize, expected: Vec<String>) -> DefError {
+        DefError::new_in(source, offset, expected, Place::default())
+    }
+
+    // DefError::new in the statement and the member at `place`
+    pub(crate) fn new_in(
+        source: &str,
+        offset: usize,
+        expected: Vec<String>,
+        place: Place,
+    ) -> DefError {
+        DefError::new_all(source, vec![(offset, expected, place)]).remove(0)
+    }
+
+    // DefError::new_in of each (offset, expected, place), in the same order. The lines of all
+    // offsets are counted in a single pass over the source.
+    pub(crate) fn new_all(source: &str, errors: Vec<Located>) -> Vec<DefError> {
+        let mut order: Vec<usize> = (0..errors.len()).collect();
+        order.sort_by_key(|&i| errors[i].0);
+        let mut order = order.into_iter().peekable();
+        let mut errors: Vec<(usize, Option<Vec<String>>, Place)> = errors
+            .into_iter()
+            .map(|(at, n, place)| (at, Some(n), place))
+            .collect();
+        let mut out = vec![None; errors.len()];
+
+        let mut line = 1;
+        let mut line_start = 0;
+        while order.peek().is_some() {
+            let line_end = source[line_start..]
+                .find('\n')
+                .map_or(source.len(), |n| line_start + n);
+            while let Some(&i) = order.peek() {
+                let (offset, expected, place) = &mut errors[i];
+                if *offset > line_end {
+                    break;
+                }
+                out[i] = Some(DefError {
+                    line,
+                    column: source[line_start..*offset].chars().count() + 1,
+                    section: text_at(source, place.statement)
+                        .and_then(|n| n.split_whitespace().next())
+                        .filter(|n| STATEMENTS.contains(n))
+                        .map(String::from),
+                    member: text_at(source, place.member)
+                        .and_then(|n| n.strip_prefix('-'))
+                        .and_then(|n| n.split_whitespace().next())
+                        .map(String::from),
+                    expected: expected.take().unwrap_or_default(),
+                    found: None,
+                    snippet: source[line_start..line_end]
+                        .trim_end_matches('\r')
+                        .to_string(),
+                });
+                order.next();
+            }
+            line += 1;
+            line_start = line_end + 1;
+        }
+        out.into_iter().map(Option::unwrap).collect()
+    }
+
+    // Build the error of a failed parse over `source`. The furthest position a parser
+    // expected something is reported, or the position nom stopped at if nothing was recorded.
+    pub(crate) fn from_nom(source: &str, err: Err<NomError<&str>>) -> DefError {
+        let (stop, kind) = match &err {
+            Err::Error(e) | Err::Failure(e) => (e.input.len(), Some(e.code)),
+            Err::Incomplete(_) => (0, None),
+        };
+        match take_expected() {
+            Some((remain, expected, place)) if remain <= stop => DefError::new_in(
+                source,
+                source.len() - remain,
+                expected.iter().map(Expected::to_string).collect(),
+                place,
+            ),
+            _ => DefError::new(
+                source,
+                source.len() - stop,
+                kind.map(|n| n.description().to_string())
+                    .into_iter()
+                    .collect(),
+            ),
+        }
+    }
+}
+
+// the source from `remain` bytes before its end, if that is in there
+fn text_at(source: &str, remain: Option<usize>) -> Option<&str> {
+    source.get(source.len().checked_sub(remain?)?..)
+}
+
+impl fmt::Display for DefError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}: ", self.line, self.column)?;
+        match self.expected.split_last() {
+            Some((last, [])) => write!(f, "expected {}", last)?,
+            Some((last, rest)) => write!(f, "expected {} or {}", rest.join(", "), last)?,
+            None => write!(f, "syntax error")?,
+        }
+        if let Some(n) = &self.found {
+            write!(f, ", found {}", n)?;
+        }
+        if let Some(n) = &self.section {
+            write!(f, " in {}", n)?;
+        }
+        if let Some(n) = &self.member {
+            write!(f, " member {}", n)?;
+        }
+        let gutter = " ".repeat(self.line.to_string().len());
+        let caret: String = self
+            .snippet
+            .chars()
+            .take(self.column - 1)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        write!(
+            f,
+            "\n{} |\n{} | {}\n{} | {}^",
+            gutter, self.line, self.snippet, gutter, caret
+        )
+    }
+}
+
+impl Error for DefError {}
+
+// Error of read_def_from: the input could not be read, or it is not DEF.
+#[derive(Debug)]
+pub enum ReadError {
+    Io(io::Error),
+    Def(DefError),
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReadError::Io(e) => e.fmt(f),
+            ReadError::Def(e) => e.fmt(f),
+        }
+    }
+}
+
+impl Error for ReadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ReadError::Io(e) => Some(e),
+            ReadError::Def(e) => Some(e),
+        }
+    }
+}
+
+impl From<io::Error> for ReadError {
+    fn from(e: io::Error) -> ReadError {
+        ReadError::Io(e)
+    }
+}
+
+impl From<DefError> for ReadError {
+    fn from(e: DefError) -> ReadError {
+        ReadError::Def(e)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+// A problem reported by parse_def_with that did not stop the parse.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub error: DefError, // where and what
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.severity {
+            Severity::Warning => write!(f, "warning: {}", self.error),
+            Severity::Error => write!(f, "error: {}", self.error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::collection::base::Place;
+    use crate::collection::def_error::DefError;
+    use crate::collection::def_parser::parse_def;
+
+    #[test]
+    fn test_def_error_new_all() {
+        let data = "NETS 2 ;\n- a ( I1 A ) ;\n- b ( I2 A ) ;\nEND NETS\n";
+        let place = |member: Option<&str>| Place {
+            statement: Some(data.len()),
+            member: member.map(|n| data.len() - data.find(n).unwrap()),
+        };
+        let errs = DefError::new_all(
+            data,
+            vec![
+                (
+                    data.find("I2").unwrap(),
+                    vec!["x".to_string()],
+                    place(Some("- b")),
+                ),
+                (0, Vec::new(), Place::default()),
+                (data.find("I1").unwrap(), Vec::new(), place(Some("- a"))),
+                (data.len(), Vec::new(), place(None)),
+            ],
+        );
+        let at: Vec<_> = errs
+            .iter()
+            .map(|n| (n.line, n.column, n.section.as_deref(), n.member.as_deref()))
+            .collect();
+        assert_eq!(
+            at,
+            vec![
+                (3, 7, Some("NETS"), Some("b")),
+                (1, 1, None, None),
+                (2, 7, Some("NETS"), Some("a")),
+                (5, 1, Some("NETS"), None)
+            ]
+        );
+        assert_eq!(errs[0].expected, vec!["x"]);
+    }
+
+    #[test]
+    fn test_def_error_member() {
+        // the member is the one the parser was in, not the first one on the line
+        let data = "DESIGN test ;
+NETS 2 ;
+- a ( I1 A ) ; - b ( I2 A ) + USE SIGNL ;
+END NETS
+END DESIGN
+";
+        let err = parse_def(data).unwrap_err();
+        assert_eq!((err.line, err.column), (3, 35));
+        assert_eq!(err.section.as_deref(), Some("NETS"));
+        assert_eq!(err.member.as_deref(), Some("b"));
+
+        // nor one starting a continuation line
+        let data = "DESIGN test ;
+COMPONENTS 1 ;
+- I1 A + PLACED ( 0
+  - 5 ) N ;
+END COMPONENTS
+END DESIGN
+";
+        let err = parse_def(data).unwrap_err();
+        assert_eq!(err.line, 4);
+        assert_eq!(err.member.as_deref(), Some("I1"));
+    }
+
+    #[test]
+    fn test_def_error() {
+        let data = "DESIGN test ;
+NETS 1 ;
+- n569 ( I1 A )
+  + USE SIGNL ;
+END NETS
+END DESIGN
+";
+        let err = parse_def(data).unwrap_err();
+        assert_eq!(err.line, 4);
+        assert_eq!(err.column, 9);
+        assert_eq!(err.section.as_deref(), Some("NETS"));
+        assert_eq!(err.member.as_deref(), Some("n569"));
+        assert!(err.expected.contains(&"`SIGNAL`".to_string()));
+        assert_eq!(err.snippet, "  + USE SIGNL ;");
+        assert!(err
+            .to_string()
+            .ends_with("\n  |\n4 |   + USE SIGNL ;\n  |         ^"));
+    }
+
+    #[test]
+    fn test_def_error_statement() {
+        let err = parse_def("DESIGN test ;\nUNITS DISTANCE MICRONS ;\nEND DESIGN\n").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 24));
+        assert_eq!(err.section.as_deref(), Some("UNITS"));
+        assert_eq!(err.member, None);
+        assert_eq!(err.expected, vec!["number"]);
+
+        let err = parse_def("DESIGN test ;\nCOMPONENT 1 ;\nEND DESIGN\n").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 1));
+        assert_eq!(err.expected, vec!["a statement or section", "`END DESIGN`"]);
+    }
+
+    #[test]
+    fn test_def_error_duplicate() {
+        let data = "DESIGN test ;
+COMPONENTS 1 ;
+- I1 A + SOURCE DIST + PLACED ( 0 0 ) N
+  + SOURCE NETLIST ;
+END COMPONENTS
+END DESIGN
+";
+        let err = parse_def(data).unwrap_err();
+        assert_eq!((err.line, err.column), (4, 3));
+        assert_eq!(err.member.as_deref(), Some("I1"));
+        assert_eq!(err.expected, vec!["`+ SOURCE` only once"]);
+        assert!(err.to_string().starts_with(
+            "line 4, column 3: expected `+ SOURCE` only once in COMPONENTS member I1"
+        ));
+
+        let err = parse_def(
+            "DESIGN test ;\nCOMPONENTS 1 ;\n- I1 A + SOURCE DIST ;\nEND COMPONENTS\nEND DESIGN\n",
+        )
+        .unwrap_err();
+        assert_eq!((err.line, err.column), (3, 22));
+        assert!(err.expected.contains(&"placement status".to_string()));
+    }
+}
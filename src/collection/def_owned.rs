@@ -0,0 +1,642 @@
+use std::borrow::Cow;
+
+// def
+use super::def_types::*;
+
+// Conversion of a parsed value into one that does not borrow the DEF text, so it can outlive
+// the input.
+pub trait IntoOwned {
+    type Owned: 'static;
+
+    fn into_owned(self) -> Self::Owned;
+}
+
+impl<'a> Def<'a> {
+    // the same Def with every name copied out of the input
+    pub fn into_owned(self) -> Def<'static> {
+        IntoOwned::into_owned(self)
+    }
+}
+
+impl<'a> IntoOwned for Cow<'a, str> {
+    type Owned = Cow<'static, str>;
+
+    fn into_owned(self) -> Cow<'static, str> {
+        Cow::Owned(Cow::into_owned(self))
+    }
+}
+
+impl<T: IntoOwned> IntoOwned for Option<T> {
+    type Owned = Option<T::Owned>;
+
+    fn into_owned(self) -> Self::Owned {
+        self.map(IntoOwned::into_owned)
+    }
+}
+
+impl<T: IntoOwned> IntoOwned for Vec<T> {
+    type Owned = Vec<T::Owned>;
+
+    fn into_owned(self) -> Self::Owned {
+        self.into_iter().map(IntoOwned::into_owned).collect()
+    }
+}
+
+impl<A: IntoOwned, B: IntoOwned> IntoOwned for (A, B) {
+    type Owned = (A::Owned, B::Owned);
+
+    fn into_owned(self) -> Self::Owned {
+        (self.0.into_owned(), self.1.into_owned())
+    }
+}
+
+impl<A: IntoOwned, B: IntoOwned, C: IntoOwned> IntoOwned for (A, B, C) {
+    type Owned = (A::Owned, B::Owned, C::Owned);
+
+    fn into_owned(self) -> Self::Owned {
+        (
+            self.0.into_owned(),
+            self.1.into_owned(),
+            self.2.into_owned(),
+        )
+    }
+}
+
+impl<A: IntoOwned, B: IntoOwned, C: IntoOwned, D: IntoOwned> IntoOwned for (A, B, C, D) {
+    type Owned = (A::Owned, B::Owned, C::Owned, D::Owned);
+
+    fn into_owned(self) -> Self::Owned {
+        (
+            self.0.into_owned(),
+            self.1.into_owned(),
+            self.2.into_owned(),
+            self.3.into_owned(),
+        )
+    }
+}
+
+// types that hold no name are returned as they are
+macro_rules! owned_as_is {
+    ($($name:ty),+ $(,)?) => {
+        $(
+            impl IntoOwned for $name {
+                type Owned = $name;
+
+                fn into_owned(self) -> $name {
+                    self
+                }
+            }
+        )+
+    };
+}
+
+owned_as_is!(
+    i32,
+    f64,
+    bool,
+    char,
+    Geometry,
+    LayerRule,
+    Location,
+    GcellGrid,
+    Placement,
+    Halo,
+    Style,
+    Orient,
+    PlacementStatus,
+    RoutingStatus,
+    Source,
+    NetUse,
+    Pattern,
+    ShapeType,
+    Direction,
+    RegionType,
+    AntennaModel,
+);
+
+// structs converted field by field. The trait is named because Cow has an inherent into_owned.
+macro_rules! owned_struct {
+    ($name:ident { $($field:ident),+ $(,)? }) => {
+        impl<'a> IntoOwned for $name<'a> {
+            type Owned = $name<'static>;
+
+            fn into_owned(self) -> $name<'static> {
+                $name {
+                    $($field: IntoOwned::into_owned(self.$field)),+
+                }
+            }
+        }
+    };
+}
+
+owned_struct!(Def {
+    version,
+    divider_char,
+    busbit_chars,
+    config,
+    vias,
+    styles,
+    ndrs,
+    regions,
+    component_mask_shift,
+    components,
+    pins,
+    pin_properties,
+    blockages,
+    slots,
+    fills,
+    special_nets,
+    nets,
+    scan_chains,
+    groups,
+    extensions,
+});
+owned_struct!(DesignConfig {
+    design_name,
+    technology,
+    units,
+    history,
+    prop_defs,
+    die_area,
+    rows,
+    tracks,
+    gcell_grids,
+});
+owned_struct!(PropDef {
+    object_type,
+    name,
+    data_type,
+    string_value,
+    int_value,
+    real_value,
+});
+owned_struct!(Row {
+    name,
+    site,
+    x,
+    y,
+    orient,
+    num_x,
+    num_y,
+    step_x,
+    step_y,
+    properties,
+});
+owned_struct!(Track {
+    axis,
+    start,
+    num,
+    step,
+    layers,
+});
+owned_struct!(Ndr {
+    name,
+    hard_spacing,
+    layers,
+    vias,
+    via_rules,
+    min_cuts,
+    properties,
+});
+owned_struct!(NdrLayer {
+    name,
+    width,
+    diag_width,
+    spacing,
+    wire_ext,
+});
+owned_struct!(Slot { layer, shapes });
+owned_struct!(Pinprop {
+    component,
+    pin,
+    properties,
+});
+owned_struct!(GeneratedVia {
+    via_rule,
+    cut_size,
+    bot_layer,
+    cut_layer,
+    top_layer,
+    cut_spacing,
+    enclosure,
+    row_col,
+    origin,
+    offset,
+    pattern,
+});
+owned_struct!(Via { name, body });
+owned_struct!(Group {
+    name,
+    components,
+    region,
+    properties,
+});
+owned_struct!(Extension { tag, body });
+owned_struct!(Region {
+    name,
+    rects,
+    region_type,
+    properties,
+});
+owned_struct!(Component {
+    name,
+    model,
+    eeqmaster,
+    source,
+    placement,
+    weight,
+    region,
+    halo,
+    route_halo,
+    properties,
+});
+owned_struct!(RouteHalo {
+    dist,
+    min_layer,
+    max_layer,
+});
+owned_struct!(RegularWireBasic {
+    layer,
+    taper_rule,
+    style,
+    route,
+});
+owned_struct!(RegularWireStmt { status, wires });
+owned_struct!(NetConnection {
+    component,
+    pin,
+    synthesized,
+});
+owned_struct!(Net {
+    name,
+    connections,
+    shield_nets,
+    vpins,
+    subnets,
+    xtalk,
+    ndr,
+    wiring,
+    source,
+    fixed_bump,
+    frequency,
+    original,
+    use_mode,
+    pattern,
+    est_cap,
+    weight,
+    properties,
+});
+owned_struct!(Vpin {
+    name,
+    layer,
+    rect,
+    location,
+});
+owned_struct!(SubNet {
+    name,
+    pins,
+    ndr,
+    wiring,
+});
+owned_struct!(SpecialWireBasic {
+    layer,
+    width,
+    shape,
+    style,
+    route,
+});
+owned_struct!(SNet {
+    name,
+    connections,
+    widths,
+    voltage,
+    wiring,
+    source,
+    fixed_bump,
+    original,
+    use_mode,
+    pattern,
+    est_cap,
+    weight,
+    properties,
+});
+owned_struct!(Pin {
+    name,
+    net,
+    special,
+    direction,
+    net_expr,
+    supply_sensitivity,
+    ground_sensitivity,
+    use_mode,
+    antenna,
+    port,
+});
+owned_struct!(PinPort { elems, location });
+owned_struct!(ScanChain {
+    name,
+    partition,
+    common_scan_pins,
+    start,
+    floating,
+    ordered,
+    stop,
+});
+owned_struct!(ScanCell {
+    name,
+    in_pin,
+    out_pin,
+    bits,
+});
+
+impl<'a> IntoOwned for PropValue<'a> {
+    type Owned = PropValue<'static>;
+
+    fn into_owned(self) -> PropValue<'static> {
+        match self {
+            PropValue::SValue(n) => PropValue::SValue(IntoOwned::into_owned(n)),
+            PropValue::RValue(n) => PropValue::RValue(n),
+            PropValue::IValue(n) => PropValue::IValue(n),
+        }
+    }
+}
+
+impl<'a> IntoOwned for ViaBody<'a> {
+    type Owned = ViaBody<'static>;
+
+    fn into_owned(self) -> ViaBody<'static> {
+        match self {
+            ViaBody::Fixed(n) => ViaBody::Fixed(n.into_owned()),
+            ViaBody::Generated(n) => ViaBody::Generated(n.into_owned()),
+        }
+    }
+}
+
+impl<'a> IntoOwned for Fill<'a> {
+    type Owned = Fill<'static>;
+
+    fn into_owned(self) -> Fill<'static> {
+        match self {
+            Fill::Layer {
+                layer,
+                mask,
+                opc,
+                shapes,
+            } => Fill::Layer {
+                layer: IntoOwned::into_owned(layer),
+                mask,
+                opc,
+                shapes,
+            },
+            Fill::Via {
+                via,
+                mask,
+                opc,
+                points,
+            } => Fill::Via {
+                via: IntoOwned::into_owned(via),
+                mask,
+                opc,
+                points,
+            },
+        }
+    }
+}
+
+impl<'a> IntoOwned for Blockage<'a> {
+    type Owned = Blockage<'static>;
+
+    fn into_owned(self) -> Blockage<'static> {
+        match self {
+            Blockage::Layer {
+                layer,
+                slots,
+                pushdown,
+                fills,
+                except_pg_net,
+                rule,
+                component,
+                mask,
+                shapes,
+            } => Blockage::Layer {
+                layer: IntoOwned::into_owned(layer),
+                slots,
+                pushdown,
+                fills,
+                except_pg_net,
+                rule,
+                component: component.into_owned(),
+                mask,
+                shapes,
+            },
+            Blockage::Placement {
+                soft,
+                partial,
+                pushdown,
+                component,
+                shapes,
+            } => Blockage::Placement {
+                soft,
+                partial,
+                pushdown,
+                component: component.into_owned(),
+                shapes,
+            },
+        }
+    }
+}
+
+impl<'a> IntoOwned for RouteElem<'a> {
+    type Owned = RouteElem<'static>;
+
+    fn into_owned(self) -> RouteElem<'static> {
+        match self {
+            RouteElem::Pt(n) => RouteElem::Pt(n),
+            RouteElem::Via {
+                point,
+                mask,
+                via,
+                orient,
+                array,
+            } => RouteElem::Via {
+                point,
+                mask,
+                via: IntoOwned::into_owned(via),
+                orient,
+                array,
+            },
+            RouteElem::Mask(n) => RouteElem::Mask(n),
+            RouteElem::Rect(n) => RouteElem::Rect(n),
+            RouteElem::Virtual(n) => RouteElem::Virtual(n),
+        }
+    }
+}
+
+impl<'a> IntoOwned for SubNetPin<'a> {
+    type Owned = SubNetPin<'static>;
+
+    fn into_owned(self) -> SubNetPin<'static> {
+        match self {
+            SubNetPin::Component(comp, pin) => {
+                SubNetPin::Component(IntoOwned::into_owned(comp), IntoOwned::into_owned(pin))
+            }
+            SubNetPin::Pin(n) => SubNetPin::Pin(IntoOwned::into_owned(n)),
+            SubNetPin::Vpin(n) => SubNetPin::Vpin(IntoOwned::into_owned(n)),
+        }
+    }
+}
+
+impl<'a> IntoOwned for SpecialWireStmt<'a> {
+    type Owned = SpecialWireStmt<'static>;
+
+    fn into_owned(self) -> SpecialWireStmt<'static> {
+        match self {
+            SpecialWireStmt::Polygon {
+                status,
+                shape,
+                mask,
+                layer,
+                points,
+            } => SpecialWireStmt::Polygon {
+                status: status.into_owned(),
+                shape,
+                mask,
+                layer: IntoOwned::into_owned(layer),
+                points,
+            },
+            SpecialWireStmt::Rect {
+                status,
+                shape,
+                mask,
+                layer,
+                rect,
+            } => SpecialWireStmt::Rect {
+                status: status.into_owned(),
+                shape,
+                mask,
+                layer: IntoOwned::into_owned(layer),
+                rect,
+            },
+            SpecialWireStmt::Via {
+                status,
+                shape,
+                mask,
+                via,
+                orient,
+                points,
+            } => SpecialWireStmt::Via {
+                status: status.into_owned(),
+                shape,
+                mask,
+                via: IntoOwned::into_owned(via),
+                orient,
+                points,
+            },
+            SpecialWireStmt::Route {
+                status,
+                shield_net,
+                wires,
+            } => SpecialWireStmt::Route {
+                status,
+                shield_net: shield_net.into_owned(),
+                wires: wires.into_owned(),
+            },
+        }
+    }
+}
+
+impl<'a> IntoOwned for PinAntenna<'a> {
+    type Owned = PinAntenna<'static>;
+
+    fn into_owned(self) -> PinAntenna<'static> {
+        match self {
+            PinAntenna::PartialMetalArea(n, layer) => {
+                PinAntenna::PartialMetalArea(n, layer.into_owned())
+            }
+            PinAntenna::PartialMetalSideArea(n, layer) => {
+                PinAntenna::PartialMetalSideArea(n, layer.into_owned())
+            }
+            PinAntenna::PartialCutArea(n, layer) => {
+                PinAntenna::PartialCutArea(n, layer.into_owned())
+            }
+            PinAntenna::DiffArea(n, layer) => PinAntenna::DiffArea(n, layer.into_owned()),
+            PinAntenna::Model(n) => PinAntenna::Model(n),
+            PinAntenna::GateArea(n, layer) => PinAntenna::GateArea(n, layer.into_owned()),
+            PinAntenna::MaxAreaCar(n, layer) => {
+                PinAntenna::MaxAreaCar(n, IntoOwned::into_owned(layer))
+            }
+            PinAntenna::MaxSideAreaCar(n, layer) => {
+                PinAntenna::MaxSideAreaCar(n, IntoOwned::into_owned(layer))
+            }
+            PinAntenna::MaxCutCar(n, layer) => {
+                PinAntenna::MaxCutCar(n, IntoOwned::into_owned(layer))
+            }
+        }
+    }
+}
+
+impl<'a> IntoOwned for Port<'a> {
+    type Owned = Port<'static>;
+
+    fn into_owned(self) -> Port<'static> {
+        match self {
+            Port::ManyPorts(n) => Port::ManyPorts(n.into_owned()),
+            Port::SinglePort(n) => Port::SinglePort(n.into_owned()),
+        }
+    }
+}
+
+impl<'a> IntoOwned for PortElem<'a> {
+    type Owned = PortElem<'static>;
+
+    fn into_owned(self) -> PortElem<'static> {
+        match self {
+            PortElem::Layer {
+                layer,
+                mask,
+                rule,
+                rect,
+            } => PortElem::Layer {
+                layer: IntoOwned::into_owned(layer),
+                mask,
+                rule,
+                rect,
+            },
+            PortElem::Polygon {
+                layer,
+                mask,
+                rule,
+                points,
+            } => PortElem::Polygon {
+                layer: IntoOwned::into_owned(layer),
+                mask,
+                rule,
+                points,
+            },
+            PortElem::Via { via, mask, point } => PortElem::Via {
+                via: IntoOwned::into_owned(via),
+                mask,
+                point,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::collection::def_owned::*;
+    use crate::collection::def_parser::parse_def;
+    use std::io::Read;
+
+    #[test]
+    fn test_into_owned() {
+        let mut input_def = std::fs::File::open("tests/def_test.def").unwrap();
+        let mut data = String::new();
+        input_def.read_to_string(&mut data).unwrap();
+
+        let owned: Def<'static> = parse_def(&data.clone()).unwrap().into_owned();
+        assert_eq!(owned, parse_def(&data).unwrap());
+        assert!(matches!(owned.config.design_name, Cow::Owned(_)));
+        assert!(matches!(owned.nets[0].name, Cow::Owned(_)));
+    }
+}
@@ -0,0 +1,204 @@
+// nom
+use nom::sequence::{pair, terminated};
+use nom::{Err, IResult};
+
+// rayon
+use rayon::prelude::*;
+
+// def
+use super::base::{
+    in_member, isolated, member_end, multispace_comment0, number, skip_member, tag, ws,
+};
+use super::common::members;
+use super::component_parser::{self, component_member};
+use super::def_types::{Component, Net, SNet};
+use super::net_parser::{self, net_member};
+use super::snet_parser::{self, snet_member};
+
+// COMPONENTS, NETS and SPECIALNETS with their members parsed on the rayon thread pool. The
+// result, errors included, is the one of the sequential section parsers.
+
+pub(crate) fn component_section(input: &str) -> IResult<&str, (i32, Vec<Component<'_>>)> {
+    parallel_section(
+        input,
+        "COMPONENTS",
+        "END COMPONENTS",
+        component_member,
+        component_parser::component_section,
+    )
+}
+
+pub(crate) fn net_section(input: &str) -> IResult<&str, (i32, Vec<Net<'_>>)> {
+    parallel_section(
+        input,
+        "NETS",
+        "END NETS",
+        net_member,
+        net_parser::net_section,
+    )
+}
+
+pub(crate) fn snet_section(input: &str) -> IResult<&str, (i32, Vec<SNet<'_>>)> {
+    parallel_section(
+        input,
+        "SPECIALNETS",
+        "END SPECIALNETS",
+        snet_member,
+        snet_parser::snet_section,
+    )
+}
+
+// section parser returning the declared count and the members
+type Section<'a, T> = fn(&'a str) -> IResult<&'a str, (i32, Vec<T>)>;
+
+// The members are found by a scan for the `;` ending each one, then each is parsed from its
+// start to the end of the input, as many0 would, and must stop where the next one starts.
+// A member that fails is parsed again on this thread, to be skipped or to fail as in
+// `sequential`. Anything else is left to `sequential` so it is reported the same.
+fn parallel_section<'a, T: Send>(
+    input: &'a str,
+    keyword: &'static str,
+    end: &'static str,
+    member: fn(&'a str) -> IResult<&'a str, T>,
+    sequential: Section<'a, T>,
+) -> IResult<&'a str, (i32, Vec<T>)> {
+    let (rest, (_, count)) = match pair(tag(keyword), terminated(number, ws(tag(";"))))(input) {
+        Ok(n) => n,
+        Err(_) => return sequential(input),
+    };
+
+    // (start, start of the next one) of each member as offsets in input
+    let mut spans = Vec::new();
+    let mut pos = input.len() - rest.len();
+    while input[pos..].starts_with('-') {
+        let next = match member_end(&input[pos..]) {
+            Some(n) => pos + n,
+            None => return sequential(input),
+        };
+        let skipped: IResult<&str, &str> = multispace_comment0(&input[next..]);
+        let next = input.len() - skipped.map_or(0, |(rest, _)| rest.len());
+        spans.push((pos, next));
+        pos = next;
+    }
+
+    let parsed: Vec<Option<T>> = spans
+        .par_iter()
+        .map(|&(start, next)| {
+            isolated(|| match member(&input[start..]) {
+                Ok((rest, n)) if input.len() - rest.len() == next => Some(n),
+                _ => None,
+            })
+        })
+        .collect();
+    let mut out = Vec::with_capacity(parsed.len());
+    for (n, &(start, next)) in parsed.into_iter().zip(&spans) {
+        if let Some(n) = n {
+            out.push(n);
+            continue;
+        }
+        match in_member(&input[start..], member) {
+            // it stops elsewhere than the scan found
+            Ok(_) => return sequential(input),
+            Err(e) => match skip_member(&input[start..]) {
+                Some(rest) if input.len() - rest.len() == next => {}
+                Some(_) => return sequential(input),
+                // members stops at the failing member and the END tag fails there
+                None => match e {
+                    Err::Error(_) => {
+                        ws(tag(end))(&input[start..])?;
+                        return sequential(input);
+                    }
+                    e => return Err(e),
+                },
+            },
+        }
+    }
+    let (rest, more) = terminated(members(member), ws(tag(end)))(&input[pos..])?;
+    out.extend(more);
+    Ok((rest, (count, out)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::collection::def_parallel::*;
+    use crate::collection::def_parser::{parse_def, parse_def_with, ParseOptions};
+
+    #[test]
+    fn test_parallel_section() {
+        let mut data = String::from("COMPONENTS 300 ;\n");
+        for i in 0..300 {
+            data += &format!(
+                "- I{} INV # comment ; {}\n  + PROPERTY note \"x ; {}\" + PLACED ( {} 0 ) N ;\n",
+                i, i, i, i
+            );
+        }
+        data += "END COMPONENTS\nEND DESIGN\n";
+        assert_eq!(
+            component_section(&data).unwrap(),
+            component_parser::component_section(&data).unwrap()
+        );
+
+        let data = "NETS 2 ;
+- n1 ( I1 A ) ( PIN in ) + USE SIGNAL ;
+- n2 ( I2 A ) + ROUTED M1 ( 0 0 ) ( 10 * ) ;
+END NETS
+";
+        assert_eq!(
+            net_section(data).unwrap(),
+            net_parser::net_section(data).unwrap()
+        );
+        let data = "SPECIALNETS 2 ;
+- VDD ( I1 VDD ) + USE POWER
+  + ROUTED M1 200 ( 0 0 ) ( 10 * ) ;
+- VSS ( I1 VSS ) + USE GROUND ;
+END SPECIALNETS
+";
+        assert_eq!(
+            snet_section(data).unwrap(),
+            snet_parser::snet_section(data).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parallel_error() {
+        let data = "DESIGN test ;
+NETS 3 ;
+- n1 ( I1 A ) ;
+- n2 ( I2 A )
+  + USE SIGNL ;
+- n3 ( I3 A ) ;
+END NETS
+END DESIGN
+";
+        let err = parse_def(data).unwrap_err();
+        assert_eq!((err.line, err.column), (5, 9));
+        assert_eq!(err.member.as_deref(), Some("n2"));
+        assert!(err.expected.contains(&"`SIGNAL`".to_string()));
+
+        // only the failing member is parsed again, the error is the one of the sequential parser
+        let mut data = String::from("NETS 300 ;\n");
+        for i in 0..300 {
+            let use_mode = if i % 100 == 50 { "SIGNL" } else { "SIGNAL" };
+            data += &format!("- n{} ( I{} A ) + USE {} ;\n", i, i, use_mode);
+        }
+        data += "END NETS\n";
+        assert_eq!(net_section(&data), net_parser::net_section(&data));
+        let no_end = data.replace("END NETS", "END");
+        assert_eq!(net_section(&no_end), net_parser::net_section(&no_end));
+        let twice = data.replace(
+            "n50 ( I50 A ) + USE SIGNL",
+            "n50 ( I50 A ) + XTALK 1 + XTALK 2",
+        );
+        assert_eq!(net_section(&twice), net_parser::net_section(&twice));
+
+        let options = ParseOptions {
+            recover: true,
+            ..ParseOptions::default()
+        };
+        let data = format!("DESIGN test ;\n{}END DESIGN\n", data);
+        let parsed = parse_def_with(&data, options).unwrap();
+        assert_eq!(parsed.def.nets.len(), 297);
+        let lines: Vec<usize> = parsed.diagnostics.iter().map(|n| n.error.line).collect();
+        assert_eq!(lines, vec![53, 153, 253]);
+    }
+}
@@ -0,0 +1,836 @@
+use std::borrow::Cow;
+
+// nom
+use nom::branch::alt;
+use nom::combinator::map;
+use nom::error::Error;
+use nom::multi::many0;
+use nom::sequence::terminated;
+use nom::{Err, IResult};
+
+// def
+use super::base::{
+    in_statement, multispace_comment0, recorded, recording, start_recovery, tag, ws, Expected,
+    Place,
+};
+use super::blockage_parser::blockage_section;
+#[cfg(not(feature = "parallel"))]
+use super::component_parser::component_section;
+use super::def_error::{DefError, Diagnostic, Located, Severity};
+#[cfg(feature = "parallel")]
+use super::def_parallel::{component_section, net_section, snet_section};
+use super::def_types::{
+    Blockage, Component, Def, Extension, Fill, GcellGrid, Group, Ndr, Net, Pin, Pinprop, PropDef,
+    Region, Row, SNet, ScanChain, Slot, Style, Track, Via,
+};
+use super::design_config_parser::{
+    design_name, die_area, gcellgrid_list, history, prop_def_section, row_rule_def_list,
+    technology_name, tracks_rule_list, units,
+};
+use super::extension_parser::extension_section;
+use super::fill_parser::fill_section;
+use super::group_parser::group_section;
+use super::header_parser::{busbit_chars, divider_char, version_num};
+use super::maskshift_parser::maskshift_section;
+#[cfg(not(feature = "parallel"))]
+use super::net_parser::net_section;
+use super::nondefaultrule_parser::ndr_section;
+use super::pin_parser::pin_section;
+use super::pinproperty_parser::pinproperty_section;
+use super::region_parser::region_section;
+use super::scanchain_parser::scanchain_section;
+use super::slot_parser::slot_section;
+#[cfg(not(feature = "parallel"))]
+use super::snet_parser::snet_section;
+use super::style_parser::style_section;
+use super::via_parser::via_section;
+
+// One top-level statement or section of a DEF file.
+pub(crate) enum DefStatement<'a> {
+    Version(f64),
+    DividerChar(&'a str),
+    BusbitChars(&'a str),
+    Design(&'a str),
+    Technology(&'a str),
+    Units(i32),
+    History(&'a str),
+    PropDefs(Vec<PropDef<'a>>),
+    DieArea(Vec<(i32, i32)>),
+    Rows(Vec<Row<'a>>),
+    Tracks(Vec<Track<'a>>),
+    GcellGrids(Vec<GcellGrid>),
+    Vias(Vec<Via<'a>>),
+    Styles(Vec<Style>),
+    Ndrs(Vec<Ndr<'a>>),
+    Regions(Vec<Region<'a>>),
+    MaskShift(Vec<&'a str>),
+    Components(Vec<Component<'a>>),
+    Pins(Vec<Pin<'a>>),
+    PinProperties(Vec<Pinprop<'a>>),
+    Blockages(Vec<Blockage<'a>>),
+    Slots(Vec<Slot<'a>>),
+    Fills(Vec<Fill<'a>>),
+    SpecialNets(Vec<SNet<'a>>),
+    Nets(Vec<Net<'a>>),
+    ScanChains(Vec<ScanChain<'a>>),
+    Groups(Vec<Group<'a>>),
+    Extension(Extension<'a>),
+}
+
+// What to do when a section holds a different number of members than its header declares,
+// ie. COMPONENTS 1234 ;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CountCheck {
+    Ignore,
+    Lenient, // report a warning
+    Strict,  // fail the parse
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseOptions {
+    pub count_check: CountCheck,
+    // skip a member that fails to parse and report it as an error diagnostic
+    pub recover: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> ParseOptions {
+        ParseOptions {
+            count_check: CountCheck::Lenient,
+            recover: false,
+        }
+    }
+}
+
+// A parsed design and the problems found on the way.
+#[derive(Debug, PartialEq)]
+pub struct ParsedDef<'a> {
+    pub def: Def<'a>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+// Parse a whole DEF file. Statements and sections may come in any order,
+// but DESIGN is required and the file must end with END DESIGN.
+#[allow(clippy::result_large_err)]
+pub fn parse_def(input: &str) -> Result<Def<'_>, DefError> {
+    parse_def_with(input, ParseOptions::default()).map(|n| n.def)
+}
+
+// parse_def with options, also returning the warnings and the members skipped in recovery
+#[allow(clippy::result_large_err)]
+pub fn parse_def_with<'a>(
+    input: &'a str,
+    options: ParseOptions,
+) -> Result<ParsedDef<'a>, DefError> {
+    // a skipped member is reported with what was expected in it
+    let (recovery, _recording) = if options.recover {
+        (Some(start_recovery()), Some(recording()))
+    } else {
+        (None, None)
+    };
+    let res = recorded(
+        input,
+        terminated(many0(def_statement), ws(tag("END DESIGN"))),
+    );
+    let skipped = recovery.map(|n| n.take_skipped()).unwrap_or_default();
+    let (remain, statements) = res.map_err(|e| statement_error(input, e))?;
+    end_of_file(input, remain)?;
+
+    // offsets of the skipped members, which count as members of the section they are in
+    let mut skipped_at: Vec<usize> = skipped.iter().map(|n| input.len() - n.0).collect();
+    skipped_at.sort_unstable();
+    // (severity, where and what, found) of each diagnostic, all located in one pass below
+    let mut found: Vec<(Severity, Located, Option<String>)> = skipped
+        .into_iter()
+        .map(|(remain, expected, place)| {
+            let expected = expected.iter().map(Expected::to_string).collect();
+            (
+                Severity::Error,
+                (input.len() - remain, expected, place),
+                None,
+            )
+        })
+        .collect();
+
+    for (i, (at, count, statement)) in statements.iter().enumerate() {
+        let start = input.len() - at.len();
+        let end = statements
+            .get(i + 1)
+            .map_or(input.len(), |n| input.len() - n.0.len());
+        let skipped =
+            skipped_at.partition_point(|&n| n < end) - skipped_at.partition_point(|&n| n < start);
+        let members = match (count, statement.members()) {
+            (Some(count), Some(members)) if *count as usize != members + skipped => {
+                (*count, members + skipped)
+            }
+            _ => continue,
+        };
+        // point at the declared number after the section keyword
+        let keyword = at.split_whitespace().next().unwrap_or_default();
+        let number = at[keyword.len()..].trim_start();
+        let offset = input.len() - number.len();
+        let expected = vec![format!("{} members", members.0)];
+        let place = Place {
+            statement: Some(at.len()),
+            member: None,
+        };
+        match options.count_check {
+            CountCheck::Ignore => {}
+            CountCheck::Lenient => found.push((
+                Severity::Warning,
+                (offset, expected, place),
+                Some(members.1.to_string()),
+            )),
+            CountCheck::Strict => {
+                let mut err = DefError::new_in(input, offset, expected, place);
+                err.found = Some(members.1.to_string());
+                return Err(err);
+            }
+        }
+    }
+    let (located, rest): (Vec<_>, Vec<_>) = found
+        .into_iter()
+        .map(|(severity, located, found)| (located, (severity, found)))
+        .unzip();
+    let mut diagnostics: Vec<Diagnostic> = DefError::new_all(input, located)
+        .into_iter()
+        .zip(rest)
+        .map(|(mut error, (severity, found))| {
+            error.found = found;
+            Diagnostic { severity, error }
+        })
+        .collect();
+
+    let mut def = Def::default();
+    let mut design = None;
+    for (_, _, statement) in statements {
+        match statement {
+            DefStatement::Version(n) => def.version = Some(n),
+            DefStatement::DividerChar(n) => def.divider_char = Some(n.into()),
+            DefStatement::BusbitChars(n) => def.busbit_chars = Some(n.into()),
+            DefStatement::Design(n) => design = Some(n),
+            DefStatement::Technology(n) => def.config.technology = Some(n.into()),
+            DefStatement::Units(n) => def.config.units = Some(n),
+            DefStatement::History(n) => def.config.history.push(n.into()),
+            DefStatement::PropDefs(n) => {
+                def.config.prop_defs.get_or_insert_with(Vec::new).extend(n)
+            }
+            DefStatement::DieArea(n) => def.config.die_area = Some(n),
+            DefStatement::Rows(n) => def.config.rows.get_or_insert_with(Vec::new).extend(n),
+            DefStatement::Tracks(n) => def.config.tracks.get_or_insert_with(Vec::new).extend(n),
+            DefStatement::GcellGrids(n) => def
+                .config
+                .gcell_grids
+                .get_or_insert_with(Vec::new)
+                .extend(n),
+            DefStatement::Vias(n) => def.vias.extend(n),
+            DefStatement::Styles(n) => def.styles.extend(n),
+            DefStatement::Ndrs(n) => def.ndrs.extend(n),
+            DefStatement::Regions(n) => def.regions.extend(n),
+            DefStatement::MaskShift(n) => {
+                def.component_mask_shift = Some(n.into_iter().map(Cow::from).collect())
+            }
+            DefStatement::Components(n) => def.components.extend(n),
+            DefStatement::Pins(n) => def.pins.extend(n),
+            DefStatement::PinProperties(n) => def.pin_properties.extend(n),
+            DefStatement::Blockages(n) => def.blockages.extend(n),
+            DefStatement::Slots(n) => def.slots.extend(n),
+            DefStatement::Fills(n) => def.fills.extend(n),
+            DefStatement::SpecialNets(n) => def.special_nets.extend(n),
+            DefStatement::Nets(n) => def.nets.extend(n),
+            DefStatement::ScanChains(n) => def.scan_chains.extend(n),
+            DefStatement::Groups(n) => def.groups.extend(n),
+            DefStatement::Extension(n) => def.extensions.push(n),
+        }
+    }
+
+    match design {
+        Some(n) => def.config.design_name = n.into(),
+        None => return Err(DefError::new(input, 0, vec!["`DESIGN`".to_string()])),
+    }
+    diagnostics.sort_by_key(|n| (n.error.line, n.error.column));
+    Ok(ParsedDef { def, diagnostics })
+}
+
+// DefError of a file that stops matching between its statements
+pub(crate) fn statement_error(input: &str, e: Err<Error<&str>>) -> DefError {
+    let mut err = DefError::from_nom(input, e);
+    // no statement got past its keyword, list them as one
+    if err.expected.iter().any(|n| n == "`END DESIGN`") {
+        err.expected = vec![
+            "a statement or section".to_string(),
+            "`END DESIGN`".to_string(),
+        ];
+    }
+    err
+}
+
+// only whitespace may follow END DESIGN
+#[allow(clippy::result_large_err)]
+pub(crate) fn end_of_file(input: &str, remain: &str) -> Result<(), DefError> {
+    if remain.trim().is_empty() {
+        return Ok(());
+    }
+    let offset = input.len() - remain.trim_start().len();
+    Err(DefError::new(
+        input,
+        offset,
+        vec!["end of file after `END DESIGN`".to_string()],
+    ))
+}
+
+impl DefStatement<'_> {
+    // number of members of a section with a declared count
+    fn members(&self) -> Option<usize> {
+        match self {
+            DefStatement::Vias(n) => Some(n.len()),
+            DefStatement::Styles(n) => Some(n.len()),
+            DefStatement::Ndrs(n) => Some(n.len()),
+            DefStatement::Regions(n) => Some(n.len()),
+            DefStatement::Components(n) => Some(n.len()),
+            DefStatement::Pins(n) => Some(n.len()),
+            DefStatement::PinProperties(n) => Some(n.len()),
+            DefStatement::Blockages(n) => Some(n.len()),
+            DefStatement::Slots(n) => Some(n.len()),
+            DefStatement::Fills(n) => Some(n.len()),
+            DefStatement::SpecialNets(n) => Some(n.len()),
+            DefStatement::Nets(n) => Some(n.len()),
+            DefStatement::ScanChains(n) => Some(n.len()),
+            DefStatement::Groups(n) => Some(n.len()),
+            _ => None,
+        }
+    }
+}
+
+// A statement with the input at its keyword and the member count declared by a section.
+fn def_statement(input: &str) -> IResult<&str, (&str, Option<i32>, DefStatement<'_>)> {
+    let (input, _) = multispace_comment0(input)?;
+    in_statement(
+        input,
+        alt((
+            map(design_statement, move |n| (input, None, n)),
+            map(def_section, move |n| (input, n.0, n.1)),
+        )),
+    )
+}
+
+// header and design config statements
+pub(crate) fn design_statement(input: &str) -> IResult<&str, DefStatement<'_>> {
+    alt((
+        map(version_num, DefStatement::Version),
+        map(divider_char, DefStatement::DividerChar),
+        map(busbit_chars, DefStatement::BusbitChars),
+        map(design_name, DefStatement::Design),
+        map(technology_name, DefStatement::Technology),
+        map(units, DefStatement::Units),
+        map(history, DefStatement::History),
+        map(prop_def_section, DefStatement::PropDefs),
+        map(die_area, DefStatement::DieArea),
+        map(row_rule_def_list, DefStatement::Rows),
+        map(tracks_rule_list, DefStatement::Tracks),
+        map(gcellgrid_list, DefStatement::GcellGrids),
+    ))(input)
+}
+
+// sections enclosed by END keyword, with their declared count
+fn def_section(input: &str) -> IResult<&str, (Option<i32>, DefStatement<'_>)> {
+    ws(alt((
+        map(via_section, |n| (Some(n.0), DefStatement::Vias(n.1))),
+        map(style_section, |n| (Some(n.0), DefStatement::Styles(n.1))),
+        map(ndr_section, |n| (Some(n.0), DefStatement::Ndrs(n.1))),
+        map(region_section, |n| (Some(n.0), DefStatement::Regions(n.1))),
+        map(maskshift_section, |n| (None, DefStatement::MaskShift(n))),
+        map(component_section, |n| {
+            (Some(n.0), DefStatement::Components(n.1))
+        }),
+        map(pin_section, |n| (Some(n.0), DefStatement::Pins(n.1))),
+        map(pinproperty_section, |n| {
+            (Some(n.0), DefStatement::PinProperties(n.1))
+        }),
+        map(blockage_section, |n| {
+            (Some(n.0), DefStatement::Blockages(n.1))
+        }),
+        map(slot_section, |n| (Some(n.0), DefStatement::Slots(n.1))),
+        map(fill_section, |n| (Some(n.0), DefStatement::Fills(n.1))),
+        map(snet_section, |n| {
+            (Some(n.0), DefStatement::SpecialNets(n.1))
+        }),
+        map(net_section, |n| (Some(n.0), DefStatement::Nets(n.1))),
+        map(scanchain_section, |n| {
+            (Some(n.0), DefStatement::ScanChains(n.1))
+        }),
+        map(group_section, |n| (Some(n.0), DefStatement::Groups(n.1))),
+        map(extension_section, |n| (None, DefStatement::Extension(n))),
+    )))(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::collection::def_error::Severity;
+    use crate::collection::def_parser::*;
+    use crate::collection::def_types::*;
+    use std::io::Read;
+
+    #[test]
+    fn test_parse_def() {
+        let mut input_def = std::fs::File::open("tests/def_test.def").unwrap();
+        let mut data = String::new();
+        input_def.read_to_string(&mut data).unwrap();
+        let def = parse_def(&data).unwrap();
+
+        assert_eq!(def.version, Some(5.7));
+        assert_eq!(def.divider_char, Some("/".into()));
+        assert_eq!(def.busbit_chars, Some("[]".into()));
+        assert_eq!(def.config.design_name, "test");
+        assert_eq!(def.config.technology, Some("tech".into()));
+        assert_eq!(def.config.units, Some(1000));
+        assert_eq!(
+            def.config.history,
+            vec!["created by tool 1.0", "modified by eco\n  script", "#1 rev"]
+        );
+        assert_eq!(def.config.die_area, Some(vec![(0, 0), (10000, 10000)]));
+        assert_eq!(def.config.rows.map(|n| n.len()), Some(2));
+        assert_eq!(def.config.tracks.map(|n| n.len()), Some(2));
+        assert_eq!(def.vias.len(), 2);
+        assert_eq!(
+            def.styles,
+            vec![Style {
+                num: 0,
+                points: vec![(30, 10), (10, 30), (-10, 30)]
+            }]
+        );
+        assert_eq!(
+            def.regions,
+            vec![Region {
+                name: "region1".into(),
+                rects: vec![((-500, -500), (300, 100))],
+                region_type: Some(RegionType::Fence),
+                properties: vec![]
+            }]
+        );
+        assert_eq!(
+            def.pins,
+            vec![Pin {
+                name: "P0".into(),
+                net: "N0".into(),
+                special: false,
+                direction: Some(Direction::Input),
+                net_expr: Some("power1 VDD1".into()),
+                supply_sensitivity: None,
+                ground_sensitivity: None,
+                use_mode: Some(NetUse::Signal),
+                antenna: vec![
+                    PinAntenna::PartialMetalArea(5, Some("M1".into())),
+                    PinAntenna::DiffArea(20, None),
+                    PinAntenna::Model(AntennaModel::Oxide2),
+                    PinAntenna::GateArea(15, Some("M1".into())),
+                    PinAntenna::MaxAreaCar(25, "M2".into()),
+                ],
+                port: Port::SinglePort(PinPort {
+                    elems: vec![PortElem::Layer {
+                        layer: "M2".into(),
+                        mask: None,
+                        rule: None,
+                        rect: ((0, 0), (30, 135))
+                    }],
+                    location: Location {
+                        status: PlacementStatus::Placed,
+                        point: (1000, 0),
+                        orient: Orient::N
+                    }
+                })
+            }]
+        );
+        assert_eq!(
+            def.slots,
+            vec![Slot {
+                layer: "M1".into(),
+                shapes: vec![Geometry::Rect(((3, 3), (6, 8)))]
+            }]
+        );
+        assert_eq!(
+            def.fills,
+            vec![Fill::Layer {
+                layer: "M1".into(),
+                mask: None,
+                opc: false,
+                shapes: vec![Geometry::Rect(((0, 2), (1, 10)))]
+            }]
+        );
+        assert_eq!(
+            def.pin_properties[0].properties,
+            vec![
+                ("intprop".into(), PropValue::IValue(1)),
+                (
+                    "comment".into(),
+                    PropValue::SValue("placed by tool v2.1".into())
+                )
+            ]
+        );
+        assert_eq!(def.ndrs.len(), 1);
+        assert_eq!(def.components.len(), 2);
+        assert_eq!(def.blockages.len(), 2);
+        assert_eq!(def.special_nets[0].wiring.len(), 3);
+        match &def.special_nets[0].wiring[1] {
+            SpecialWireStmt::Route {
+                status, shield_net, ..
+            } => assert_eq!(
+                (*status, shield_net.as_deref()),
+                (RoutingStatus::Shield, Some("N0"))
+            ),
+            _ => unreachable!(),
+        }
+        assert_eq!(
+            def.special_nets[0].wiring[2],
+            SpecialWireStmt::Via {
+                status: Some((RoutingStatus::Fixed, None)),
+                shape: Some(ShapeType::Stripe),
+                mask: None,
+                via: "M1_M2".into(),
+                orient: Some(Orient::N),
+                points: vec![(0, 0), (10000, 0)]
+            }
+        );
+        assert_eq!(def.nets[0].connections.len(), 2);
+        assert_eq!(
+            def.nets[0].wiring[0].wires[1].route,
+            vec![
+                RouteElem::Pt((Some(1000), Some(100), None)),
+                RouteElem::Pt((Some(100), None, None)),
+                RouteElem::Mask(2),
+                RouteElem::Rect((-5, -5, 5, 5)),
+                RouteElem::Virtual((100, 300)),
+                RouteElem::Pt((None, Some(400), None)),
+            ]
+        );
+        assert_eq!(def.scan_chains.len(), 1);
+        assert_eq!(def.groups[0].components, vec!["I1", "I2"]);
+        assert_eq!(
+            def.extensions,
+            vec![Extension {
+                tag: "tool".into(),
+                body: "\n  tool specific ; text # kept\n".into(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_def_name_chars() {
+        let data = "DIVIDERCHAR \"|\" ;
+BUSBITCHARS \"<>\" ;
+DESIGN test ;
+COMPONENTS 1 ;
+- top|u1<3> A + PLACED ( 0 0 ) N ;
+END COMPONENTS
+NETS 1 ;
+- top|data<0> ( top|u1<3> Z<0> ) ;
+END NETS
+END DESIGN
+";
+        let def = parse_def(data).unwrap();
+        assert_eq!(def.components[0].name, "top|u1<3>");
+        assert_eq!(def.nets[0].name, "top|data<0>");
+        assert_eq!(def.nets[0].connections[0].pin, "Z<0>");
+        assert_eq!(
+            def.split_name(&def.nets[0].name),
+            HierName {
+                path: vec!["top"],
+                name: "data",
+                bus_index: Some(0)
+            }
+        );
+
+        assert_eq!(
+            HierName::split("i1/i2[2]/clk", '/', "[]"),
+            HierName {
+                path: vec!["i1", "i2[2]"],
+                name: "clk",
+                bus_index: None
+            }
+        );
+
+        // the default "/" and "[]" are plain characters here
+        let data = data.replace("top|u1<3> A", "top/u1[3] A");
+        let def = parse_def(&data).unwrap();
+        assert_eq!(
+            def.split_name(&def.components[0].name),
+            HierName {
+                path: vec![],
+                name: "top/u1[3]",
+                bus_index: None
+            }
+        );
+        assert_eq!(
+            HierName::split("a\\/b/c\\[1\\]", '/', "[]"),
+            HierName {
+                path: vec!["a\\/b"],
+                name: "c\\[1\\]",
+                bus_index: None
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_def_mask() {
+        let data = "VERSION 5.8 ;
+DESIGN test ;
+PINS 1 ;
+- P0 + NET N0
+  + LAYER M1 MASK 2 ( 0 0 ) ( 10 10 )
+  + POLYGON M2 MASK 1 ( 0 0 ) ( 0 10 ) ( 10 0 )
+  + VIA V12 MASK 021 ( 5 5 )
+  + PLACED ( 100 0 ) N ;
+END PINS
+VIAS 1 ;
+- V12 + RECT M1 + MASK 1 ( -5 -5 ) ( 5 5 ) + RECT M2 ( -5 -5 ) ( 5 5 ) ;
+END VIAS
+BLOCKAGES 1 ;
+- LAYER M1 + SPACING 3 + MASK 2 RECT ( 0 0 ) ( 5 5 ) ;
+END BLOCKAGES
+FILLS 2 ;
+- LAYER M1 + MASK 1 + OPC RECT ( 0 0 ) ( 5 5 ) ;
+- VIA V12 + MASK 031 ( 5 5 ) ;
+END FILLS
+SPECIALNETS 1 ;
+- VDD
+  + FIXED + SHAPE RING + MASK 2 + POLYGON M1 ( 0 0 ) ( 0 10 ) ( 10 0 )
+  + SHIELD VSS + RECT M2 ( 0 0 ) ( 10 10 )
+  + ROUTED M1 20 ( 10 0 ) MASK 3 ( 10 20 ) MASK 031 V12 ;
+END SPECIALNETS
+NETS 1 ;
+- N0 ( PIN P0 ) + ROUTED M1 ( 0 0 ) MASK 1 ( 0 10 ) MASK 12 V12 ;
+END NETS
+END DESIGN
+";
+        let def = parse_def(data).unwrap();
+        let port = match &def.pins[0].port {
+            Port::SinglePort(n) => n,
+            Port::ManyPorts(_) => unreachable!(),
+        };
+        assert_eq!(
+            port.elems,
+            vec![
+                PortElem::Layer {
+                    layer: "M1".into(),
+                    mask: Some(2),
+                    rule: None,
+                    rect: ((0, 0), (10, 10))
+                },
+                PortElem::Polygon {
+                    layer: "M2".into(),
+                    mask: Some(1),
+                    rule: None,
+                    points: vec![(0, 0), (0, 10), (10, 0)]
+                },
+                PortElem::Via {
+                    via: "V12".into(),
+                    mask: Some(21),
+                    point: (5, 5)
+                },
+            ]
+        );
+        assert_eq!(
+            def.vias[0].body,
+            ViaBody::Fixed(vec![
+                ("M1".into(), Some(1), Geometry::Rect(((-5, -5), (5, 5)))),
+                ("M2".into(), None, Geometry::Rect(((-5, -5), (5, 5)))),
+            ])
+        );
+        match &def.blockages[0] {
+            Blockage::Layer { rule, mask, .. } => {
+                assert_eq!((*rule, *mask), (Some(LayerRule::Spacing(3)), Some(2)))
+            }
+            Blockage::Placement { .. } => unreachable!(),
+        }
+        assert_eq!(
+            def.fills,
+            vec![
+                Fill::Layer {
+                    layer: "M1".into(),
+                    mask: Some(1),
+                    opc: true,
+                    shapes: vec![Geometry::Rect(((0, 0), (5, 5)))]
+                },
+                Fill::Via {
+                    via: "V12".into(),
+                    mask: Some(31),
+                    opc: false,
+                    points: vec![(5, 5)]
+                },
+            ]
+        );
+        assert_eq!(
+            def.special_nets[0].wiring,
+            vec![
+                SpecialWireStmt::Polygon {
+                    status: Some((RoutingStatus::Fixed, None)),
+                    shape: Some(ShapeType::Ring),
+                    mask: Some(2),
+                    layer: "M1".into(),
+                    points: vec![(0, 0), (0, 10), (10, 0)]
+                },
+                SpecialWireStmt::Rect {
+                    status: Some((RoutingStatus::Shield, Some("VSS".into()))),
+                    shape: None,
+                    mask: None,
+                    layer: "M2".into(),
+                    rect: ((0, 0), (10, 10))
+                },
+                SpecialWireStmt::Route {
+                    status: RoutingStatus::Routed,
+                    shield_net: None,
+                    wires: vec![SpecialWireBasic {
+                        layer: "M1".into(),
+                        width: 20,
+                        shape: None,
+                        style: None,
+                        route: vec![
+                            RouteElem::Pt((Some(10), Some(0), None)),
+                            RouteElem::Mask(3),
+                            RouteElem::Via {
+                                point: Some((Some(10), Some(20), None)),
+                                mask: Some(31),
+                                via: "V12".into(),
+                                orient: None,
+                                array: None,
+                            },
+                        ]
+                    }]
+                },
+            ]
+        );
+        assert_eq!(
+            def.nets[0].wiring[0].wires[0].route,
+            vec![
+                RouteElem::Pt((Some(0), Some(0), None)),
+                RouteElem::Mask(1),
+                RouteElem::Via {
+                    point: Some((Some(0), Some(10), None)),
+                    mask: Some(12),
+                    via: "V12".into(),
+                    orient: None,
+                    array: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_def_requires_design() {
+        assert!(parse_def("VERSION 5.7 ;\nEND DESIGN\n").is_err());
+        assert!(parse_def("DESIGN test ;\n").is_err());
+        assert!(parse_def("DESIGN test ;\nEND DESIGN\n").is_ok());
+    }
+
+    #[test]
+    fn test_count_check() {
+        let data = "DESIGN test ;
+COMPONENTS 3 ;
+- I1 A + PLACED ( 0 0 ) N ;
+- I2 A + PLACED ( 0 0 ) N ;
+END COMPONENTS
+NETS 0 ;
+END NETS
+END DESIGN
+";
+        let parsed = parse_def_with(data, ParseOptions::default()).unwrap();
+        assert_eq!(parsed.def.components.len(), 2);
+        assert_eq!(parsed.diagnostics.len(), 1);
+        let warning = &parsed.diagnostics[0];
+        assert_eq!(warning.severity, Severity::Warning);
+        assert_eq!((warning.error.line, warning.error.column), (2, 12));
+        assert_eq!(warning.error.section.as_deref(), Some("COMPONENTS"));
+        assert!(warning
+            .to_string()
+            .starts_with("warning: line 2, column 12: expected 3 members, found 2 in COMPONENTS"));
+
+        let strict = ParseOptions {
+            count_check: CountCheck::Strict,
+            ..ParseOptions::default()
+        };
+        let err = parse_def_with(data, strict).unwrap_err();
+        assert_eq!(err, warning.error);
+
+        let ignore = ParseOptions {
+            count_check: CountCheck::Ignore,
+            ..ParseOptions::default()
+        };
+        assert!(parse_def_with(data, ignore).unwrap().diagnostics.is_empty());
+        assert!(parse_def(data).is_ok());
+
+        let mut input_def = std::fs::File::open("tests/def_test.def").unwrap();
+        let mut data = String::new();
+        input_def.read_to_string(&mut data).unwrap();
+        let parsed = parse_def_with(&data, strict).unwrap();
+        assert!(parsed.diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_recover() {
+        let data = "DESIGN test ;
+COMPONENTS 3 ;
+- I1 A + PLACED ( 0 0 ) N ;
+- I2 A + SOURCE DIST + PLACED ( 0 0 ) N
+  + SOURCE USER ;
+- I3 A + PLACED ( 0 0 ) N ;
+END COMPONENTS
+NETS 3 ;
+- n1 ( I1 A ) ;
+- n2 ( I2 A )
+  + USE SIGNL ;
+- n3 ( I3 A ) + PROPERTY note \"a ; b\" ;
+END NETS
+END DESIGN
+";
+        let err = parse_def(data).unwrap_err();
+        assert_eq!((err.line, err.column), (5, 3));
+
+        let options = ParseOptions {
+            count_check: CountCheck::Ignore,
+            recover: true,
+        };
+        let parsed = parse_def_with(data, options).unwrap();
+        let names: Vec<&str> = parsed.def.components.iter().map(|n| &*n.name).collect();
+        assert_eq!(names, vec!["I1", "I3"]);
+        let names: Vec<&str> = parsed.def.nets.iter().map(|n| &*n.name).collect();
+        assert_eq!(names, vec!["n1", "n3"]);
+
+        assert_eq!(parsed.diagnostics.len(), 2);
+        let error = &parsed.diagnostics[0];
+        assert_eq!(error.severity, Severity::Error);
+        assert_eq!(error.error, err);
+        let error = &parsed.diagnostics[1].error;
+        assert_eq!((error.line, error.column), (11, 9));
+        assert_eq!(error.member.as_deref(), Some("n2"));
+        assert!(error.expected.contains(&"`SIGNAL`".to_string()));
+        assert!(parsed.diagnostics[1]
+            .to_string()
+            .starts_with("error: line 11, column 9: expected"));
+
+        // the skipped members are part of the declared numbers
+        let options = ParseOptions {
+            recover: true,
+            ..ParseOptions::default()
+        };
+        let parsed = parse_def_with(data, options).unwrap();
+        let severities: Vec<Severity> = parsed.diagnostics.iter().map(|n| n.severity).collect();
+        assert_eq!(severities, vec![Severity::Error, Severity::Error]);
+        let options = ParseOptions {
+            count_check: CountCheck::Strict,
+            recover: true,
+        };
+        let parsed = parse_def_with(data, options).unwrap();
+        assert_eq!(parsed.def.nets.len(), 2);
+        assert_eq!(parsed.diagnostics.len(), 2);
+        let data = data.replace("NETS 3 ;", "NETS 4 ;");
+        let err = parse_def_with(&data, options).unwrap_err();
+        assert_eq!((err.line, err.found.as_deref()), (8, Some("3")));
+        // a recovering parse that fails leaves no recovery behind
+        assert_eq!(parse_def(&data).unwrap_err().line, 5);
+
+        // only members are skipped
+        let data = data.replace("NETS 4 ;", "NETS three ;");
+        let err = parse_def_with(&data, options).unwrap_err();
+        assert_eq!((err.line, err.column), (8, 6));
+    }
+}
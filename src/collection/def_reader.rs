@@ -0,0 +1,451 @@
+use std::io::BufRead;
+
+// nom
+use nom::error::Error;
+use nom::sequence::delimited;
+use nom::{Err, IResult};
+
+// def
+use super::base::{
+    in_member, in_statement, multispace_comment0, number, peek_expected, recorded, tag, ws,
+};
+use super::blockage_parser::blockage_member;
+use super::component_parser::component_member;
+use super::def_error::{DefError, ReadError};
+use super::def_parser::{design_statement, end_of_file, statement_error, DefStatement};
+use super::def_types::{
+    Blockage, Component, Extension, Fill, GcellGrid, Group, Ndr, Net, Pin, Pinprop, PropDef,
+    Region, Row, SNet, ScanChain, Slot, Style, Track, Via,
+};
+use super::extension_parser::extension_section;
+use super::fill_parser::fill_member;
+use super::group_parser::group_member;
+use super::maskshift_parser::maskshift_section;
+use super::net_parser::net_member;
+use super::nondefaultrule_parser::ndr_member;
+use super::pin_parser::pin_member;
+use super::pinproperty_parser::pinproperty_member;
+use super::region_parser::region_member;
+use super::scanchain_parser::scanchain_member;
+use super::slot_parser::slot_member;
+use super::snet_parser::snet_member;
+use super::style_parser::style_member;
+use super::via_parser::via_member;
+
+// Handlers called by read_def as it walks a DEF file, like the callbacks of the Si2 defrReader.
+// Every handler does nothing by default, implement the ones of interest. Members of a section
+// are handed over one at a time and dropped afterwards.
+#[allow(unused_variables)]
+pub trait DefVisitor {
+    fn on_version(&mut self, version: f64) {}
+    fn on_divider_char(&mut self, divider_char: &str) {}
+    fn on_busbit_chars(&mut self, busbit_chars: &str) {}
+    fn on_design(&mut self, name: &str) {}
+    fn on_technology(&mut self, name: &str) {}
+    fn on_units(&mut self, units: i32) {}
+    fn on_history(&mut self, history: &str) {}
+    fn on_prop_def(&mut self, prop_def: &PropDef) {}
+    fn on_die_area(&mut self, points: &[(i32, i32)]) {}
+    fn on_row(&mut self, row: &Row) {}
+    fn on_track(&mut self, track: &Track) {}
+    fn on_gcell_grid(&mut self, grid: &GcellGrid) {}
+    fn on_component_mask_shift(&mut self, layers: &[&str]) {}
+    fn on_extension(&mut self, extension: &Extension) {}
+
+    // start of a section with a member count, ie. COMPONENTS 1234 ;
+    fn on_section_start(&mut self, keyword: &str, count: i32) {}
+    // after the last member of a section
+    fn on_section_end(&mut self, keyword: &str) {}
+
+    fn on_via(&mut self, via: &Via) {}
+    fn on_style(&mut self, style: &Style) {}
+    fn on_ndr(&mut self, ndr: &Ndr) {}
+    fn on_region(&mut self, region: &Region) {}
+    fn on_component(&mut self, component: &Component) {}
+    fn on_pin(&mut self, pin: &Pin) {}
+    fn on_pin_property(&mut self, pinprop: &Pinprop) {}
+    fn on_blockage(&mut self, blockage: &Blockage) {}
+    fn on_slot(&mut self, slot: &Slot) {}
+    fn on_fill(&mut self, fill: &Fill) {}
+    fn on_special_net(&mut self, snet: &SNet) {}
+    fn on_net(&mut self, net: &Net) {}
+    fn on_scan_chain(&mut self, scan_chain: &ScanChain) {}
+    fn on_group(&mut self, group: &Group) {}
+}
+
+// Walk a whole DEF file and call the handlers of `visitor` in file order. The file is
+// checked as by parse_def, a DefError is returned at the first mismatch.
+#[allow(clippy::result_large_err)]
+pub fn read_def<V: DefVisitor>(input: &str, visitor: &mut V) -> Result<(), DefError> {
+    let mut place = Place::default();
+    let mut rest = input;
+    loop {
+        let (remain, done) = recorded(rest, |n| step(n, visitor, &mut place))
+            .map_err(|e| place.locate(statement_error(input, e), 0))?;
+        rest = remain;
+        if done {
+            break;
+        }
+    }
+    end_of_file(input, rest)?;
+    if !place.design {
+        return Err(DefError::new(input, 0, vec!["`DESIGN`".to_string()]));
+    }
+    Ok(())
+}
+
+// read_def over any BufRead, ie. a BufReader of a file. Whole lines are buffered until they
+// hold the next statement or member, which is handed to `visitor` and dropped.
+#[allow(clippy::result_large_err)]
+pub fn read_def_from<R: BufRead, V: DefVisitor>(
+    mut reader: R,
+    visitor: &mut V,
+) -> Result<(), ReadError> {
+    let mut place = Place::default();
+    let mut buf = String::new();
+    let mut start = 0; // parse position, buf[..start] is the part of its first line already read
+    let mut lines = 0; // lines dropped from the front of buf
+    let mut eof = false;
+    loop {
+        if !eof {
+            let len = buf.len();
+            if reader.read_line(&mut buf)? == 0 {
+                eof = true;
+            } else if !may_end(&buf[len..]) {
+                continue;
+            }
+        }
+
+        loop {
+            match recorded(&buf[start..], |n| step(n, visitor, &mut place)) {
+                Ok((rest, false)) => start = buf.len() - rest.len(),
+                Ok((rest, true)) => {
+                    start = buf.len() - rest.len();
+                    // only whitespace may follow END DESIGN
+                    reader.read_to_string(&mut buf)?;
+                    let mut res = end_of_file(&buf, &buf[start..]);
+                    if res.is_ok() && !place.design {
+                        res = Err(DefError::new(&buf, 0, vec!["`DESIGN`".to_string()]));
+                    }
+                    return res.map_err(|err| place.locate(err, lines).into());
+                }
+                // a member or statement running past the buffer fails at its end
+                Err(e) if !eof && at_end(&buf, &e) => break,
+                Err(e) => {
+                    return Err(place.locate(statement_error(&buf, e), lines).into());
+                }
+            }
+        }
+
+        // drop the lines read, keep the start of the current one for error snippets
+        if let Some(n) = buf[..start].rfind('\n') {
+            lines += buf[..n].matches('\n').count() + 1;
+            buf.drain(..=n);
+            start -= n + 1;
+        }
+    }
+}
+
+// a line that may end a statement or a member
+fn may_end(line: &str) -> bool {
+    line.contains(';') || line.trim_start().starts_with("END")
+}
+
+// Whether a parse over `source` failed at its end, only whitespace and comments after the
+// position DefError::from_nom would report.
+fn at_end(source: &str, e: &Err<Error<&str>>) -> bool {
+    let stop = match e {
+        Err::Error(e) | Err::Failure(e) => e.input.len(),
+        Err::Incomplete(_) => 0,
+    };
+    let remain = peek_expected().map_or(stop, |n| n.min(stop));
+    let rest = &source[source.len() - remain..];
+    multispace_comment0::<Error<&str>>(rest).is_ok_and(|n| n.0.is_empty())
+}
+
+// Where read_def is in a file: in a section or between statements.
+#[derive(Default)]
+struct Place {
+    section: Option<(&'static str, &'static str)>, // keyword and END tag, from SECTIONS
+    design: bool,                                  // DESIGN was read
+}
+
+impl Place {
+    // Error of read_def or read_def_from in a window of the file after `lines` lines. The
+    // window starts inside the section being read, so its header is not in there.
+    fn locate(&self, mut err: DefError, lines: usize) -> DefError {
+        if let (None, Some((section, _))) = (&err.section, self.section) {
+            err.section = Some(section.to_string());
+        }
+        err.line += lines;
+        err
+    }
+}
+
+// sections of `- ... ;` members, keyword and END tag
+const SECTIONS: [(&str, &str); 14] = [
+    ("VIAS", "END VIAS"),
+    ("STYLES", "END STYLES"),
+    ("NONDEFAULTRULES", "END NONDEFAULTRULES"),
+    ("REGIONS", "END REGIONS"),
+    ("COMPONENTS", "END COMPONENTS"),
+    ("PINS", "END PINS"),
+    ("PINPROPERTIES", "END PINPROPERTIES"),
+    ("BLOCKAGES", "END BLOCKAGES"),
+    ("SLOTS", "END SLOTS"),
+    ("FILLS", "END FILLS"),
+    ("SPECIALNETS", "END SPECIALNETS"),
+    ("NETS", "END NETS"),
+    ("SCANCHAINS", "END SCANCHAINS"),
+    ("GROUPS", "END GROUPS"),
+];
+
+// Read the next statement, section header, member or section end and hand it to `visitor`.
+// Returns true after END DESIGN.
+fn step<'a, V: DefVisitor>(
+    input: &'a str,
+    visitor: &mut V,
+    place: &mut Place,
+) -> IResult<&'a str, bool> {
+    // read_def_from may stop a read after the whitespace following the last step
+    let (input, _) = multispace_comment0(input)?;
+    if let Some((keyword, end)) = place.section {
+        return match in_member(input, |n| member::<V>(keyword)(n, visitor)) {
+            Ok((rest, _)) => Ok((rest, false)),
+            Err(Err::Error(_)) => {
+                let (rest, _) = ws(tag(end))(input)?;
+                visitor.on_section_end(keyword);
+                place.section = None;
+                Ok((rest, false))
+            }
+            Err(e) => Err(e),
+        };
+    }
+
+    in_statement(input, |input| {
+        match design_statement(input) {
+            Ok((rest, statement)) => {
+                visit_statement(statement, visitor, &mut place.design);
+                return Ok((rest, false));
+            }
+            Err(Err::Error(_)) => {}
+            Err(e) => return Err(e),
+        }
+
+        for &(keyword, end) in SECTIONS.iter() {
+            match delimited(tag(keyword), number, ws(tag(";")))(input) {
+                Ok((rest, count)) => {
+                    visitor.on_section_start(keyword, count);
+                    place.section = Some((keyword, end));
+                    return Ok((rest, false));
+                }
+                Err(Err::Error(_)) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        if let Ok((rest, layers)) = maskshift_section(input) {
+            visitor.on_component_mask_shift(&layers);
+            return Ok((rest, false));
+        }
+        if let Ok((rest, extension)) = extension_section(input) {
+            visitor.on_extension(&extension);
+            return Ok((rest, false));
+        }
+        let (rest, _) = ws(tag("END DESIGN"))(input)?;
+        Ok((rest, true))
+    })
+}
+
+// parser of one member of the section with keyword `section`, handing it to the visitor
+fn member<'a, V: DefVisitor>(section: &str) -> fn(&'a str, &mut V) -> IResult<&'a str, ()> {
+    match section {
+        "VIAS" => |i, v| visit(i, v, via_member, V::on_via),
+        "STYLES" => |i, v| visit(i, v, style_member, V::on_style),
+        "NONDEFAULTRULES" => |i, v| visit(i, v, ndr_member, V::on_ndr),
+        "REGIONS" => |i, v| visit(i, v, region_member, V::on_region),
+        "COMPONENTS" => |i, v| visit(i, v, component_member, V::on_component),
+        "PINS" => |i, v| visit(i, v, pin_member, V::on_pin),
+        "PINPROPERTIES" => |i, v| visit(i, v, pinproperty_member, V::on_pin_property),
+        "BLOCKAGES" => |i, v| visit(i, v, blockage_member, V::on_blockage),
+        "SLOTS" => |i, v| visit(i, v, slot_member, V::on_slot),
+        "FILLS" => |i, v| visit(i, v, fill_member, V::on_fill),
+        "SPECIALNETS" => |i, v| visit(i, v, snet_member, V::on_special_net),
+        "NETS" => |i, v| visit(i, v, net_member, V::on_net),
+        "SCANCHAINS" => |i, v| visit(i, v, scanchain_member, V::on_scan_chain),
+        "GROUPS" => |i, v| visit(i, v, group_member, V::on_group),
+        _ => unreachable!("{} is not in SECTIONS", section),
+    }
+}
+
+fn visit<'a, O, V>(
+    input: &'a str,
+    visitor: &mut V,
+    member: fn(&'a str) -> IResult<&'a str, O>,
+    on: fn(&mut V, &O),
+) -> IResult<&'a str, ()> {
+    let (rest, n) = member(input)?;
+    on(visitor, &n);
+    Ok((rest, ()))
+}
+
+fn visit_statement<V: DefVisitor>(statement: DefStatement, visitor: &mut V, design: &mut bool) {
+    match statement {
+        DefStatement::Version(n) => visitor.on_version(n),
+        DefStatement::DividerChar(n) => visitor.on_divider_char(n),
+        DefStatement::BusbitChars(n) => visitor.on_busbit_chars(n),
+        DefStatement::Design(n) => {
+            *design = true;
+            visitor.on_design(n)
+        }
+        DefStatement::Technology(n) => visitor.on_technology(n),
+        DefStatement::Units(n) => visitor.on_units(n),
+        DefStatement::History(n) => visitor.on_history(n),
+        DefStatement::PropDefs(n) => n.iter().for_each(|n| visitor.on_prop_def(n)),
+        DefStatement::DieArea(n) => visitor.on_die_area(&n),
+        DefStatement::Rows(n) => n.iter().for_each(|n| visitor.on_row(n)),
+        DefStatement::Tracks(n) => n.iter().for_each(|n| visitor.on_track(n)),
+        DefStatement::GcellGrids(n) => n.iter().for_each(|n| visitor.on_gcell_grid(n)),
+        // sections are read by step
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::collection::def_parser::parse_def;
+    use crate::collection::def_reader::*;
+    use std::io::{BufReader, Read};
+
+    // counts what it is shown
+    #[derive(Debug, Default, PartialEq)]
+    struct Counter {
+        design: String,
+        sections: Vec<(String, i32)>,
+        ends: usize,
+        components: Vec<String>,
+        nets: usize,
+        special_nets: usize,
+        pins: usize,
+        extensions: usize,
+    }
+
+    impl DefVisitor for Counter {
+        fn on_design(&mut self, name: &str) {
+            self.design = name.to_string();
+        }
+        fn on_section_start(&mut self, keyword: &str, count: i32) {
+            self.sections.push((keyword.to_string(), count));
+        }
+        fn on_section_end(&mut self, _: &str) {
+            self.ends += 1;
+        }
+        fn on_component(&mut self, component: &Component) {
+            self.components.push(component.name.to_string());
+        }
+        fn on_net(&mut self, _: &Net) {
+            self.nets += 1;
+        }
+        fn on_special_net(&mut self, _: &SNet) {
+            self.special_nets += 1;
+        }
+        fn on_pin(&mut self, _: &Pin) {
+            self.pins += 1;
+        }
+        fn on_extension(&mut self, _: &Extension) {
+            self.extensions += 1;
+        }
+    }
+
+    #[test]
+    fn test_read_def() {
+        let mut input_def = std::fs::File::open("tests/def_test.def").unwrap();
+        let mut data = String::new();
+        input_def.read_to_string(&mut data).unwrap();
+        let def = parse_def(&data).unwrap();
+
+        let mut counter = Counter::default();
+        read_def(&data, &mut counter).unwrap();
+        assert_eq!(counter.design, "test");
+        assert_eq!(counter.sections.len(), counter.ends);
+        assert_eq!(counter.sections[0], ("PINS".to_string(), 1));
+        assert!(counter.sections.contains(&("COMPONENTS".to_string(), 2)));
+        assert_eq!(counter.components, vec!["I1", "I2"]);
+        assert_eq!(counter.nets, def.nets.len());
+        assert_eq!(counter.special_nets, def.special_nets.len());
+        assert_eq!(counter.pins, def.pins.len());
+        assert_eq!(counter.extensions, 1);
+
+        // the same errors as parse_def
+        let bad = data.replace("+ USE SIGNAL\n  + ROUTED", "+ USE SIGNL\n  + ROUTED");
+        assert_eq!(
+            read_def(&bad, &mut Counter::default()).unwrap_err(),
+            parse_def(&bad).unwrap_err()
+        );
+        let err = read_def(
+            "DESIGN test ;\nNETS 1 ;\nEND DESIGN\n",
+            &mut Counter::default(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            parse_def("DESIGN test ;\nNETS 1 ;\nEND DESIGN\n").unwrap_err()
+        );
+        assert!(read_def("VERSION 5.7 ;\nEND DESIGN\n", &mut Counter::default()).is_err());
+    }
+
+    // a reader returning at most 3 bytes at a time
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let n = self.0.len().min(buf.len()).min(3);
+            buf[..n].copy_from_slice(&self.0[..n]);
+            self.0 = &self.0[n..];
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn test_read_def_from() {
+        let mut input_def = std::fs::File::open("tests/def_test.def").unwrap();
+        let mut data = String::new();
+        input_def.read_to_string(&mut data).unwrap();
+
+        let mut expected = Counter::default();
+        read_def(&data, &mut expected).unwrap();
+        // a few bytes per read, lines and members span many reads
+        let mut counter = Counter::default();
+        read_def_from(BufReader::new(Trickle(data.as_bytes())), &mut counter).unwrap();
+        assert_eq!(counter, expected);
+
+        let from = |data: &str| match read_def_from(data.as_bytes(), &mut Counter::default()) {
+            Err(ReadError::Def(e)) => e,
+            res => panic!("{:?}", res),
+        };
+        let bad = data.replace("+ USE SIGNAL\n  + ROUTED", "+ USE SIGNL\n  + ROUTED");
+        assert_eq!(from(&bad), parse_def(&bad).unwrap_err());
+        match read_def_from(
+            BufReader::new(Trickle(bad.as_bytes())),
+            &mut Counter::default(),
+        ) {
+            Err(ReadError::Def(e)) => assert_eq!(e, parse_def(&bad).unwrap_err()),
+            res => panic!("{:?}", res),
+        }
+        let bad = data.replace("+ WEIGHT 2", "+ WEIGHT 2 + WEIGHT 3");
+        assert_eq!(from(&bad), parse_def(&bad).unwrap_err());
+        let bad = data.replace("ENDEXT", "");
+        assert_eq!(from(&bad), parse_def(&bad).unwrap_err());
+        let bad = data.replace("END DESIGN", "END DESIGN\nVERSION 5.8 ;");
+        assert_eq!(from(&bad), parse_def(&bad).unwrap_err());
+        let bad = data.replace("END NETS", "");
+        assert_eq!(from(&bad).line, parse_def(&bad).unwrap_err().line);
+        // lines already dropped are not searched for the statement
+        let err = from("DESIGN test ;\n");
+        assert_eq!((err.line, err.column, err.section), (2, 1, None));
+        assert_eq!(
+            err.expected,
+            parse_def("DESIGN test ;\n").unwrap_err().expected
+        );
+        assert_eq!(from("END DESIGN\n"), parse_def("END DESIGN\n").unwrap_err());
+    }
+}
@@ -1,442 +1,830 @@
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
 // Define reusable type alias
 
-pub type DesignConfig<'a> = (
-    &'a str,                  // design name
-    Option<&'a str>,          // technology name
-    Option<i32>,              // units
-    Option<Vec<PropDef<'a>>>, // propdef
-    Option<Vec<(i32, i32)>>,  // die area
-    Option<Vec<Row<'a>>>,     // rows
-    Option<Vec<Track<'a>>>,   // tracks
-    Option<Vec<GcellGrid>>,   // gcellgrid
-);
-
-pub type PropDef<'a> = (
-    &'a str, // object type of property. ie, design, region, group, component,...
-    &'a str, // property name
-    (
-        char, // data type of property
-        Option<&'a str>,
-        Option<(i32, Option<(i32, i32)>)>,
-        Option<(f64, Option<(f64, f64)>)>,
-    ),
-);
-
-pub type Row<'a> = (
-    &'a str, // name of row rule
-    &'a str, // row rule type
-    i32,     //  the origin of rule scope along axis X
-    i32,     // the origin of rule scope along axis Y
-    OrientT, // orient of row rule
-    i32,     // step number along axis X
-    i32,     // step number along axis Y
-    i32,     // step size along axis X
-    i32,     // step size along axis Y
-    Option<Properties<'a>>,
-);
-
-pub type Track<'a> = (
-    char,                 // axis. 'X' or 'Y'
-    i32,                  // the origin of gcell grid along axis X/Y
-    i32,                  // the step number of track
-    i32,                  // the step size of track
-    Option<Vec<&'a str>>, // the metal layer of track
-);
-
-pub type GcellGrid = (
-    char, // axis. 'X' or 'Y'
-    i32,  // the origin of gcell grid along axis X/Y
-    i32,  // the step number of gcell grid
-    i32,  // the step size of gcell grid
-);
+// The whole DEF file. Sections absent from the file are left empty.
+#[derive(Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Def<'a> {
+    pub version: Option<f64>,               // VERSION
+    pub divider_char: Option<Cow<'a, str>>, // DIVIDERCHAR
+    pub busbit_chars: Option<Cow<'a, str>>, // BUSBITCHARS
+    pub config: DesignConfig<'a>,           // DESIGN, TECHNOLOGY, UNITS, ..., GCELLGRID
+    pub vias: Vec<Via<'a>>,
+    pub styles: Vec<Style>,
+    pub ndrs: Vec<Ndr<'a>>,
+    pub regions: Vec<Region<'a>>,
+    pub component_mask_shift: Option<Vec<Cow<'a, str>>>, // COMPONENTMASKSHIFT layers
+    pub components: Vec<Component<'a>>,
+    pub pins: Vec<Pin<'a>>,
+    pub pin_properties: Vec<Pinprop<'a>>,
+    pub blockages: Vec<Blockage<'a>>,
+    pub slots: Vec<Slot<'a>>,
+    pub fills: Vec<Fill<'a>>,
+    pub special_nets: Vec<SNet<'a>>,
+    pub nets: Vec<Net<'a>>,
+    pub scan_chains: Vec<ScanChain<'a>>,
+    pub groups: Vec<Group<'a>>,
+    pub extensions: Vec<Extension<'a>>, // BEGINEXT
+}
+
+impl<'a> Def<'a> {
+    // split a name with the DIVIDERCHAR and BUSBITCHARS of this file
+    pub fn split_name<'b>(&self, name: &'b str) -> HierName<'b> {
+        HierName::split(
+            name,
+            self.divider_char
+                .as_deref()
+                .and_then(|n| n.chars().next())
+                .unwrap_or('/'),
+            self.busbit_chars.as_deref().unwrap_or("[]"),
+        )
+    }
+}
+
+// A hierarchical name split at the divider character, ie. i1/i2/data[3]
+#[derive(Debug, PartialEq)]
+pub struct HierName<'a> {
+    pub path: Vec<&'a str>,     // levels above the last one, ie. ["i1", "i2"]
+    pub name: &'a str,          // last level without its bus index, ie. "data"
+    pub bus_index: Option<i32>, // ie. 3
+}
+
+impl<'a> HierName<'a> {
+    // Escaped dividers and bus bit characters, ie. \/ and \[, are part of the name.
+    // The parts are raw slices, see unescape.
+    pub fn split(name: &'a str, divider: char, busbit_chars: &str) -> HierName<'a> {
+        let mut bus_chars = busbit_chars.chars();
+        let (open, close) = (bus_chars.next(), bus_chars.next());
+
+        let mut path = Vec::new();
+        let mut start = 0; // start of the current level
+        let mut bus_start = None; // unescaped open char in the current level
+        let mut chars = name.char_indices();
+        while let Some((i, c)) = chars.next() {
+            if c == '\\' {
+                chars.next();
+            } else if c == divider {
+                path.push(&name[start..i]);
+                start = i + c.len_utf8();
+                bus_start = None;
+            } else if Some(c) == open {
+                bus_start = Some(i);
+            }
+        }
+
+        let last = &name[start..];
+        let bus = match (bus_start, close) {
+            (Some(i), Some(close)) if last.ends_with(close) && !last.ends_with('\\') => {
+                let i = i - start;
+                last[i + open.map_or(1, char::len_utf8)..last.len() - close.len_utf8()]
+                    .parse()
+                    .ok()
+                    .map(|index| (&last[..i], index))
+            }
+            _ => None,
+        };
+        match bus {
+            Some((n, index)) => HierName {
+                path,
+                name: n,
+                bus_index: Some(index),
+            },
+            None => HierName {
+                path,
+                name: last,
+                bus_index: None,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DesignConfig<'a> {
+    pub design_name: Cow<'a, str>,
+    pub technology: Option<Cow<'a, str>>,
+    pub units: Option<i32>,         // UNITS DISTANCE MICRONS
+    pub history: Vec<Cow<'a, str>>, // text of each HISTORY statement
+    pub prop_defs: Option<Vec<PropDef<'a>>>,
+    pub die_area: Option<Pts>,
+    pub rows: Option<Vec<Row<'a>>>,
+    pub tracks: Option<Vec<Track<'a>>>,
+    pub gcell_grids: Option<Vec<GcellGrid>>,
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PropDef<'a> {
+    pub object_type: Cow<'a, str>, // object type of property. ie, design, region, group, component,...
+    pub name: Cow<'a, str>,        // property name
+    pub data_type: char,           // data type of property
+    pub string_value: Option<Cow<'a, str>>,
+    pub int_value: Option<RangeValue<i32>>, // RANGE and value of INTEGER
+    pub real_value: Option<RangeValue<f64>>, // RANGE and value of REAL
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Row<'a> {
+    pub name: Cow<'a, str>, // name of row rule
+    pub site: Cow<'a, str>, // row rule type
+    pub x: i32,             // the origin of rule scope along axis X
+    pub y: i32,             // the origin of rule scope along axis Y
+    pub orient: Orient,
+    pub num_x: i32,  // step number along axis X
+    pub num_y: i32,  // step number along axis Y
+    pub step_x: i32, // step size along axis X
+    pub step_y: i32, // step size along axis Y
+    pub properties: Option<Properties<'a>>,
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Track<'a> {
+    pub axis: char,                        // 'X' or 'Y'
+    pub start: i32,                        // the origin of track along axis X/Y
+    pub num: i32,                          // the step number of track
+    pub step: i32,                         // the step size of track
+    pub layers: Option<Vec<Cow<'a, str>>>, // the metal layer of track
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GcellGrid {
+    pub axis: char, // 'X' or 'Y'
+    pub start: i32, // the origin of gcell grid along axis X/Y
+    pub num: i32,   // the step number of gcell grid
+    pub step: i32,  // the step size of gcell grid
+}
 
 pub type Properties<'a> = Vec<(
     // property defined in DESIGN Section
-    &'a str, // property name
+    Cow<'a, str>, // property name
     PropValue<'a>,
 )>;
 
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PropValue<'a> {
-    SValue(&'a str),
+    SValue(Cow<'a, str>),
     RValue(f64),
     IValue(i32),
 }
 
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Geometry {
-    Rect(((i32, i32), (i32, i32))),
-    Polygon(Vec<(i32, i32)>),
+    Rect(Rect),
+    Polygon(Pts),
 }
 
-pub type Location = (
-    LocAtrriT,  // location attribute
-    (i32, i32), // location
-    OrientT,    // orient
-);
+// Location of a pin port or virtual pin
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Location {
+    pub status: PlacementStatus,
+    pub point: (i32, i32),
+    pub orient: Orient,
+}
 
 pub type Pts = Vec<(i32, i32)>;
 pub type Rect = ((i32, i32), (i32, i32));
+pub type RangeValue<T> = (Option<(T, T)>, Option<T>); // [RANGE min max] [value]
+
 // NONDEFAULTRULES
-pub type Ndr<'a> = (
-    &'a str, // ndr name
-    (
-        bool, // whether hardspacing
-        Vec<(
-            // Layer rule.
-            &'a str,     // layer name
-            i32,         // width. integer
-            Option<i32>, // diagwidth. integer
-            Option<i32>, // spacing. integer
-            Option<i32>, // wireext. integer
-        )>,
-        Vec<&'a str>,        // VIA. specifiy previous vias to use this rule
-        Vec<&'a str>,        // VIARULE.
-        Vec<(&'a str, i32)>, // (curLayer,minCuts). specifiy the minimuum number of cuts allowed for via using this cut layer
-        Properties<'a>,
-    ),
-);
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ndr<'a> {
+    pub name: Cow<'a, str>,
+    pub hard_spacing: bool,
+    pub layers: Vec<NdrLayer<'a>>,
+    pub vias: Vec<Cow<'a, str>>, // VIA. specifiy previous vias to use this rule
+    pub via_rules: Vec<Cow<'a, str>>, // VIARULE.
+    pub min_cuts: Vec<(Cow<'a, str>, i32)>, // (curLayer,minCuts). specifiy the minimuum number of cuts allowed for via using this cut layer
+    pub properties: Properties<'a>,
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NdrLayer<'a> {
+    pub name: Cow<'a, str>,
+    pub width: i32,
+    pub diag_width: Option<i32>,
+    pub spacing: Option<i32>,
+    pub wire_ext: Option<i32>,
+}
 
 // SLOT
-pub type Slot<'a> = (
-    &'a str,       // layer name
-    Vec<Geometry>, // rect/polygon
-);
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Slot<'a> {
+    pub layer: Cow<'a, str>,
+    pub shapes: Vec<Geometry>,
+}
 
 // PINPROPERTIES
-pub type Pinprop<'a> = (
-    Option<&'a str>, // PIN or componentName. if PIN, it's None, else Some(componentName)
-    &'a str,         // pinName of exact PIN or component
-    Properties<'a>,
-);
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Pinprop<'a> {
+    pub component: Option<Cow<'a, str>>, // None for PIN, else Some(componentName)
+    pub pin: Cow<'a, str>,               // pinName of exact PIN or component
+    pub properties: Properties<'a>,
+}
 
 // VIAS
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ViaBody<'a> {
     Fixed(
         Vec<(
-            &'a str,  // Layer Name
-            Geometry, //
+            Cow<'a, str>, // Layer Name
+            Option<i32>,  // MASK maskNum
+            Geometry,     //
         )>,
     ),
-    Generated(
-        (
-            &'a str,                      // viaRule
-            (i32, i32),                   // cutSize. (xSize, ySize)
-            (&'a str, &'a str, &'a str),  // LAYERS. (botmetalLayer,cutLayer,topMetalLayer)
-            (i32, i32),                   // cutSpacing. (xCutSpacing,yCutSpacing)
-            (i32, i32, i32, i32),         // endClosure. (xBotEnc, yBotEnc, xTopEnc, yTopEnc)
-            Option<(i32, i32)>,           // ROWCOL. (numCutRows, NumCutCols)
-            Option<(i32, i32)>,           // ORIGIN. (xOffset, yOffset)
-            Option<(i32, i32, i32, i32)>, // OFFSET. (xBotOffset, yBotOffset, xTopOffset, yTopOffset)
-            Option<&'a str>,              // PATTERN. cutPattern
-        ),
-    ),
+    Generated(GeneratedVia<'a>),
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GeneratedVia<'a> {
+    pub via_rule: Cow<'a, str>,
+    pub cut_size: (i32, i32), // (xSize, ySize)
+    pub bot_layer: Cow<'a, str>,
+    pub cut_layer: Cow<'a, str>,
+    pub top_layer: Cow<'a, str>,
+    pub cut_spacing: (i32, i32),         // (xCutSpacing, yCutSpacing)
+    pub enclosure: (i32, i32, i32, i32), // (xBotEnc, yBotEnc, xTopEnc, yTopEnc)
+    pub row_col: Option<(i32, i32)>,     // (numCutRows, NumCutCols)
+    pub origin: Option<(i32, i32)>,      // (xOffset, yOffset)
+    pub offset: Option<(i32, i32, i32, i32)>, // (xBotOffset, yBotOffset, xTopOffset, yTopOffset)
+    pub pattern: Option<Cow<'a, str>>,   // cutPattern
 }
 
-pub type Via<'a> = (
-    &'a str, // viaName
-    ViaBody<'a>,
-);
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Via<'a> {
+    pub name: Cow<'a, str>,
+    pub body: ViaBody<'a>,
+}
 
 // GROUPS
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Group<'a> {
+    pub name: Cow<'a, str>,
+    pub components: Vec<Cow<'a, str>>, // compNamePattern. A component name, a list of component names or a pattern for a set of components
+    pub region: Cow<'a, str>,
+    pub properties: Properties<'a>,
+}
 
-pub type Group<'a> = (
-    &'a str,      // groupName
-    Vec<&'a str>, // compNamePattern. A component name, a list of component names or a pattern for a set of components
-    &'a str,      // regionName
-    Properties<'a>,
-);
+// BEGINEXT
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Extension<'a> {
+    pub tag: Cow<'a, str>,  // tag name without the quotes
+    pub body: Cow<'a, str>, // raw text between the tag and ENDEXT
+}
 
 // REGIONS
-pub type Region<'a> = (
-    (
-        &'a str,   // region name
-        Vec<Rect>, // define a region as one or more rectangular areas specified by pairs of coordinate points
-    ),
-    (
-        Option<RegionT>, // TYPE. FENCE or GUIDE
-        Properties<'a>,
-    ),
-);
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Region<'a> {
+    pub name: Cow<'a, str>,
+    pub rects: Vec<Rect>, // define a region as one or more rectangular areas specified by pairs of coordinate points
+    pub region_type: Option<RegionType>, // TYPE. FENCE or GUIDE
+    pub properties: Properties<'a>,
+}
 
 // FILL
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Fill<'a> {
-    Layer(
-        (
-            &'a str, // name of layer
-            bool,    // whether OPC
-            Vec<Geometry>,
-        ),
-    ),
-    Via(
-        (
-            &'a str, // name of via
-            bool,    // whether OPC
-            Pts,
-        ),
-    ),
+    Layer {
+        layer: Cow<'a, str>,
+        mask: Option<i32>, // MASK maskNum
+        opc: bool,
+        shapes: Vec<Geometry>,
+    },
+    Via {
+        via: Cow<'a, str>,
+        mask: Option<i32>, // MASK viaMaskNum
+        opc: bool,
+        points: Pts,
+    },
+}
+
+// SPACING minSpacing or DESIGNRULEWIDTH effectiveWidth of a layer blockage or a pin shape
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LayerRule {
+    Spacing(i32),
+    DesignRuleWidth(i32),
 }
 
 // BLOCKAGE
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Blockage<'a> {
-    Layer(
-        (
-            &'a str,
-            (
-                bool, // SLOTS. Whether creates a blockage on the specified layer where slots cannot be placed.
-                bool, // PUSHDOWN. Specifies that whether the blockage was pushed down into the block from the top level of the design.
-                bool, // FILLS. Whether creates a blockage on the specified layer where metal fills cannot be placed.
-                bool, // EXCEPTPGNET. Indicates that whether the blockage only blocks signal net routing, and does not block power or ground net routing.
-                Option<i32>, // SPACING or DESIGNRULEWIDTH. minimum spacing allowed between the blockage and any other routing shape
-                Option<&'a str>, // COMPONENT. component with which to associate a blockage.
-            ),
-            Vec<Geometry>,
-        ),
-    ),
-    Placement(
-        (
-            (
-                bool, //SOFT. Indicates that whether the initial placement should not use the area, but later phases, such as timing optimization or clock tree synthesis, can use the blockage area.
-                Option<f64>, // PARTIAL. Indicates that the initial placement should not use more than partial percentage of the blockage area for standard cells.
-                bool, //PUSHDOWN. Specifies that whether the blockage was pushed down into the block from the top level of the design.
-                Option<&'a str>, // COMPONENT. component with which to associate a blockage.
-            ),
-            Vec<Geometry>,
-        ),
-    ),
+    Layer {
+        layer: Cow<'a, str>,
+        slots: bool, // Whether creates a blockage on the specified layer where slots cannot be placed.
+        pushdown: bool, // Specifies that whether the blockage was pushed down into the block from the top level of the design.
+        fills: bool, // Whether creates a blockage on the specified layer where metal fills cannot be placed.
+        except_pg_net: bool, // Indicates that whether the blockage only blocks signal net routing, and does not block power or ground net routing.
+        rule: Option<LayerRule>, // SPACING minimum spacing allowed between the blockage and any other routing shape, or DESIGNRULEWIDTH
+        component: Option<Cow<'a, str>>, // component with which to associate a blockage.
+        mask: Option<i32>,       // MASK maskNum. mask number of the blockage shapes
+        shapes: Vec<Geometry>,
+    },
+    Placement {
+        soft: bool, // Indicates that whether the initial placement should not use the area, but later phases, such as timing optimization or clock tree synthesis, can use the blockage area.
+        partial: Option<f64>, // Indicates that the initial placement should not use more than partial percentage of the blockage area for standard cells.
+        pushdown: bool, // Specifies that whether the blockage was pushed down into the block from the top level of the design.
+        component: Option<Cow<'a, str>>, // component with which to associate a blockage.
+        shapes: Vec<Geometry>,
+    },
 }
 
 // COMPONENT
-pub type Component<'a> = (
-    (
-        &'a str, // component(instance) name
-        &'a str, // model name
-    ), // basic
-    (
-        Option<&'a str>,                            // EEQMASTER
-        Option<SourceT>,                            // SOURCE
-        (LocAtrriT, Option<((i32, i32), OrientT)>), // location and attribute
-        Option<i32>,                                // WEIGHT
-        Option<&'a str>,                            // REGION
-        Option<(bool, i32, i32, i32, i32)>,         // HALO
-        Option<(i32, &'a str, &'a str)>,            // ROUTEDHALO
-        Properties<'a>,                             // Properties
-    ), //feature
-);
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Component<'a> {
+    pub name: Cow<'a, str>,  // component(instance) name
+    pub model: Cow<'a, str>, // model name
+    pub eeqmaster: Option<Cow<'a, str>>,
+    pub source: Option<Source>,
+    pub placement: Placement,
+    pub weight: Option<i32>,
+    pub region: Option<Cow<'a, str>>,
+    pub halo: Option<Halo>,
+    pub route_halo: Option<RouteHalo<'a>>,
+    pub properties: Properties<'a>,
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Placement {
+    pub status: PlacementStatus,
+    pub location: Option<((i32, i32), Orient)>, // None when UNPLACED
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Halo {
+    pub soft: bool,
+    pub left: i32,
+    pub bottom: i32,
+    pub right: i32,
+    pub top: i32,
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RouteHalo<'a> {
+    pub dist: i32,
+    pub min_layer: Cow<'a, str>,
+    pub max_layer: Cow<'a, str>,
+}
 
 // NET
 
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum RouteElem<'a> {
     Pt(RtPt<'a>),
-    Via(
-        (
-            RtPt<'a>,
-            &'a str, // viaName
-        ),
-    ),
+    Via {
+        point: Option<RtPt<'a>>, // None for a via right after another via
+        mask: Option<i32>,       // MASK viaMaskNum, ie. 31 for MASK 031
+        via: Cow<'a, str>,
+        orient: Option<Orient>,
+        array: Option<(i32, i32, i32, i32)>, // DO numX BY numY STEP stepX stepY, special wiring only
+    },
+    Mask(i32), // MASK maskNum of the wire to the next point or of the next RECT
+    Rect((i32, i32, i32, i32)), // RECT ( deltax1 deltay1 deltax2 deltay2 ) from the last point, regular wiring only
+    Virtual((i32, i32)),        // VIRTUAL ( x y ), regular wiring only
 }
 
 pub type RtPt<'a> = (Option<i32>, Option<i32>, Option<i32>);
 
 pub type RouteBody<'a> = Vec<RouteElem<'a>>;
 
-pub type RegularWireBasic<'a> = (
-    &'a str,         // layer name
-    Option<&'a str>, // TAPERRULE
-    Option<i32>,     // stylNum,
-    RouteBody<'a>,
-);
-
-pub type RegularWireStmt<'a> = (
-    LocAtrriT, // 0: cover; 1: fixed; 2: routed; 3: noshield
-    Vec<RegularWireBasic<'a>>,
-);
-
-pub type Net<'a> = (
-    (
-        &'a str,                               // netName
-        Vec<(Option<&'a str>, &'a str, bool)>, // componentName, pinName, whether synthesized.
-    ), // basic
-    (
-        Vec<&'a str>,    // SHIELDNET
-        Vec<Vpin<'a>>,   // VPIN
-        Vec<SubNet<'a>>, // SUBNET
-        Option<i32>,     //XTALK
-        Option<&'a str>, // NONDEFAULTRULE
-        Vec<RegularWireStmt<'a>>,
-        NetProperty<'a>,
-    ), // feature
-);
-
-pub type Vpin<'a> = (
-    // VPIN
-    &'a str,                  // vpin name
-    &'a str,                  // layer name
-    ((i32, i32), (i32, i32)), // vpin geometry
-    LocAtrriT,                // 0: placed ; 1: fixed ; 2: covered
-    (i32, i32),               // vpin location
-    OrientT,                  // orient
-);
-
-pub type SubNet<'a> = (
-    // SUBNET
-    (
-        &'a str, // subnet name
-        Vec<(
-            Option<&'a str>, // compName or None
-            Option<&'a str>, // VPIN name or None
-            Option<&'a str>, // PIN name
-        )>,
-    ), // basic
-    (
-        Option<&'a str>,          // nondefaultrule
-        Vec<RegularWireStmt<'a>>, // regular wiring
-    ), // feature
-);
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RegularWireBasic<'a> {
+    pub layer: Cow<'a, str>,
+    pub taper_rule: Option<Cow<'a, str>>, // TAPERRULE
+    pub style: Option<i32>,
+    pub route: RouteBody<'a>,
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RegularWireStmt<'a> {
+    pub status: RoutingStatus, // COVER, FIXED, ROUTED or NOSHIELD
+    pub wires: Vec<RegularWireBasic<'a>>,
+}
+
+// ( compName pinName [+ SYNTHESIZED] ) in NET and SPECIALNET
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NetConnection<'a> {
+    pub component: Option<Cow<'a, str>>, // None for PIN
+    pub pin: Cow<'a, str>,
+    pub synthesized: bool,
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Net<'a> {
+    pub name: Cow<'a, str>,
+    pub connections: Vec<NetConnection<'a>>,
+    pub shield_nets: Vec<Cow<'a, str>>,
+    pub vpins: Vec<Vpin<'a>>,
+    pub subnets: Vec<SubNet<'a>>,
+    pub xtalk: Option<i32>,
+    pub ndr: Option<Cow<'a, str>>, // NONDEFAULTRULE
+    pub wiring: Vec<RegularWireStmt<'a>>,
+    pub source: Option<Source>,
+    pub fixed_bump: bool,
+    pub frequency: Option<f64>,
+    pub original: Option<Cow<'a, str>>,
+    pub use_mode: Option<NetUse>,
+    pub pattern: Option<Pattern>,
+    pub est_cap: Option<i32>,
+    pub weight: Option<i32>,
+    pub properties: Properties<'a>,
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Vpin<'a> {
+    pub name: Cow<'a, str>,
+    pub layer: Cow<'a, str>,
+    pub rect: Rect,
+    pub location: Location,
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SubNet<'a> {
+    pub name: Cow<'a, str>,
+    pub pins: Vec<SubNetPin<'a>>,
+    pub ndr: Option<Cow<'a, str>>, // NONDEFAULTRULE
+    pub wiring: Vec<RegularWireStmt<'a>>,
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SubNetPin<'a> {
+    Component(Cow<'a, str>, Cow<'a, str>), // compName and pinName
+    Pin(Cow<'a, str>),
+    Vpin(Cow<'a, str>),
+}
 
 // Special Net
-pub type SpecialWireBasic<'a> = (
-    &'a str,        // layer name
-    i32,            // route width
-    Option<ShapeT>, // shape code
-    Option<i32>,    // stylNum,
-    RouteBody<'a>,
-);
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpecialWireBasic<'a> {
+    pub layer: Cow<'a, str>,
+    pub width: i32,
+    pub shape: Option<ShapeType>,
+    pub style: Option<i32>,
+    pub route: RouteBody<'a>,
+}
 
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SpecialWireStmt<'a> {
-    Polygon((&'a str, Pts)),
-    Rect((&'a str, Rect)),
-    Route(
-        (
-            LocAtrriT, // location attribute
-            Vec<SpecialWireBasic<'a>>,
-        ),
-    ),
+    Polygon {
+        status: Option<(RoutingStatus, Option<Cow<'a, str>>)>, // COVER, FIXED, ROUTED or SHIELD with shieldNetName
+        shape: Option<ShapeType>,
+        mask: Option<i32>, // MASK maskNum
+        layer: Cow<'a, str>,
+        points: Pts,
+    },
+    Rect {
+        status: Option<(RoutingStatus, Option<Cow<'a, str>>)>, // COVER, FIXED, ROUTED or SHIELD with shieldNetName
+        shape: Option<ShapeType>,
+        mask: Option<i32>, // MASK maskNum
+        layer: Cow<'a, str>,
+        rect: Rect,
+    },
+    Via {
+        status: Option<(RoutingStatus, Option<Cow<'a, str>>)>, // COVER, FIXED, ROUTED or SHIELD with shieldNetName
+        shape: Option<ShapeType>,
+        mask: Option<i32>, // MASK viaMaskNum
+        via: Cow<'a, str>,
+        orient: Option<Orient>,
+        points: Pts, // a via is placed at each point
+    },
+    Route {
+        status: RoutingStatus,            // COVER, FIXED, ROUTED or SHIELD
+        shield_net: Option<Cow<'a, str>>, // shieldNetName of SHIELD
+        wires: Vec<SpecialWireBasic<'a>>,
+    },
 }
 
-pub type SNet<'a> = (
-    (
-        &'a str,                               // special netName
-        Vec<(Option<&'a str>, &'a str, bool)>, // componentName, pinName, whether synthesized.
-    ), // basic
-    (
-        Option<i32>, // volts
-        Vec<SpecialWireStmt<'a>>,
-        SNetProperty<'a>,
-    ), // feature
-);
-
-// NetProperty that used in NET
-pub type NetProperty<'a> = (
-    Option<SourceT>,  //SOURCE. 0: DIST; 1: NETLIST; 2:TEST; 3:TIMING; 4:USER
-    bool,             // FIXEDBUMP
-    Option<f64>,      // FREQUENCY
-    Option<&'a str>,  // ORIGINAL
-    Option<UseModeT>, // USE. 0: ANALOG; 1:CLOCK; 2:GROUND; 3:POWER; 4:RESET; 5: SCAN; 6:SIGNAL; 7: TIEOFF
-    Option<PatternT>, // PATTERN. 0: BALANCED; 1:STEINER; 2:TRUNK; 3:WIREDLOGIC
-    Option<i32>,      // ESTCAP
-    Option<i32>,      // WEIGHT
-    Properties<'a>,
-);
-
-// NetProperty that used in Special Net
-pub type SNetProperty<'a> = (
-    Option<SourceT>,  //SOURCE. 0: DIST; 1: NETLIST; 2:TEST; 3:TIMING; 4:USER
-    bool,             // FIXEDBUMP
-    Option<&'a str>,  // ORIGINAL
-    Option<UseModeT>, // USE. 0: ANALOG; 1:CLOCK; 2:GROUND; 3:POWER; 4:RESET; 5: SCAN; 6:SIGNAL; 7: TIEOFF
-    Option<PatternT>, // PATTERN. 0: BALANCED; 1:STEINER; 2:TRUNK; 3:WIREDLOGIC
-    Option<i32>,      // ESTCAP
-    Option<i32>,      // WEIGHT
-    Properties<'a>,
-);
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SNet<'a> {
+    pub name: Cow<'a, str>,
+    pub connections: Vec<NetConnection<'a>>,
+    pub widths: Vec<(Cow<'a, str>, i32)>, // + WIDTH layerName width
+    pub voltage: Option<i32>,
+    pub wiring: Vec<SpecialWireStmt<'a>>,
+    pub source: Option<Source>,
+    pub fixed_bump: bool,
+    pub original: Option<Cow<'a, str>>,
+    pub use_mode: Option<NetUse>,
+    pub pattern: Option<Pattern>,
+    pub est_cap: Option<i32>,
+    pub weight: Option<i32>,
+    pub properties: Properties<'a>,
+}
 
 // Style
-pub type Style = (
-    i32, // style number
-    Pts,
-);
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Style {
+    pub num: i32, // style number
+    pub points: Pts,
+}
 
 // Pin
-pub type Pin<'a> = (
-    (&'a str, &'a str), // pinName & netName
-    (
-        bool,                // Whether special
-        Option<DirecttionT>, // direction
-        Option<&'a str>,     // NetExpre
-        Option<&'a str>,     // PowerPin name
-        Option<&'a str>,     // GroundPin name
-        Option<PinModeT>,    // pin mode
-        // antenna not supported
-        Port<'a>,
-    ), //
-);
-
-// #[derive(Debug, PartialEq)]
-// pub enum PinAntenna<'a> {
-//     PartialMetalArea((i32, Option<&'a str>)),
-//     PartialMetalSideArea((i32, Option<&'a str>)),
-//     PartialCutArea((i32, Option<&'a str>)),
-//     DiffArea((i32, Option<&'a str>)),
-//     Model(Option<i32>),
-//     GateArea((i32, Option<&'a str>)),
-//     MaxAreaCar((i32, &'a str)),
-//     MaxSideAreaCar((i32, &'a str)),
-//     MaxCutCar((i32, &'a str)),
-// }
-
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Pin<'a> {
+    pub name: Cow<'a, str>,
+    pub net: Cow<'a, str>,
+    pub special: bool,
+    pub direction: Option<Direction>,
+    pub net_expr: Option<Cow<'a, str>>,           // NETEXPR
+    pub supply_sensitivity: Option<Cow<'a, str>>, // PowerPin name
+    pub ground_sensitivity: Option<Cow<'a, str>>, // GroundPin name
+    pub use_mode: Option<NetUse>,
+    pub antenna: Vec<PinAntenna<'a>>, // in file order, values after ANTENNAMODEL belong to that model
+    pub port: Port<'a>,
+}
+
+// antenna attributes of a pin, value and LAYER name
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PinAntenna<'a> {
+    PartialMetalArea(i32, Option<Cow<'a, str>>),
+    PartialMetalSideArea(i32, Option<Cow<'a, str>>),
+    PartialCutArea(i32, Option<Cow<'a, str>>),
+    DiffArea(i32, Option<Cow<'a, str>>),
+    Model(AntennaModel),
+    GateArea(i32, Option<Cow<'a, str>>),
+    MaxAreaCar(i32, Cow<'a, str>),
+    MaxSideAreaCar(i32, Cow<'a, str>),
+    MaxCutCar(i32, Cow<'a, str>),
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Port<'a> {
-    ManyPorts(Vec<(Vec<PortElem<'a>>, Location)>),
-    SinglePort((Vec<PortElem<'a>>, Location)),
+    ManyPorts(Vec<PinPort<'a>>),
+    SinglePort(PinPort<'a>),
 }
 
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PinPort<'a> {
+    pub elems: Vec<PortElem<'a>>,
+    pub location: Location,
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PortElem<'a> {
-    Layer((&'a str, Option<i32>, ((i32, i32), (i32, i32)))),
-    Polygon((&'a str, Option<i32>, Vec<(i32, i32)>)),
-    Via((&'a str, (i32, i32))),
-}
-
-pub type ScanChain<'a> = (
-    &'a str,                        // name
-    Option<(&'a str, Option<i32>)>, // partition
-    Option<(
-        &'a str, // IN pin
-        &'a str, // OUT PIN
-    )>, // commonscanpins
-    Option<(
-        Option<&'a str>, // if from component, then has name; whether none
-        &'a str,         // pin name
-    )>, // start.
-    Option<(
-        &'a str,     // scancell
-        &'a str,     //  in pin
-        &'a str,     // out pin
-        Option<i32>, // max bits
-    )>, // float
-    Option<(
-        &'a str,     // scancell
-        &'a str,     // in pin
-        &'a str,     // out pin
-        Option<i32>, // max bits
-    )>, // ordered
-    Option<(
-        Option<&'a str>, // if from component, then has name; whether none
-        &'a str,         // pin name
-    )>, // stop.
-);
-
-type OrientT = i8;
-type LocAtrriT = i8;
-type PinModeT = i8;
-type DirecttionT = i8;
-type SourceT = i8;
-type UseModeT = i8;
-type PatternT = i8;
-type ShapeT = i8;
-type RegionT = i8;
+    Layer {
+        layer: Cow<'a, str>,
+        mask: Option<i32>,       // MASK maskNum
+        rule: Option<LayerRule>, // SPACING or DESIGNRULEWIDTH
+        rect: Rect,
+    },
+    Polygon {
+        layer: Cow<'a, str>,
+        mask: Option<i32>,       // MASK maskNum
+        rule: Option<LayerRule>, // SPACING or DESIGNRULEWIDTH
+        points: Pts,
+    },
+    Via {
+        via: Cow<'a, str>,
+        mask: Option<i32>, // MASK viaMaskNum
+        point: (i32, i32),
+    },
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScanChain<'a> {
+    pub name: Cow<'a, str>,
+    pub partition: Option<(Cow<'a, str>, Option<i32>)>, // partition name and MAXBITS
+    pub common_scan_pins: Option<(Cow<'a, str>, Cow<'a, str>)>, // IN pin and OUT pin
+    pub start: Option<(Option<Cow<'a, str>>, Cow<'a, str>)>, // component name (None for PIN) and pin name
+    pub floating: Option<ScanCell<'a>>,
+    pub ordered: Option<ScanCell<'a>>,
+    pub stop: Option<(Option<Cow<'a, str>>, Cow<'a, str>)>, // component name (None for PIN) and pin name
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScanCell<'a> {
+    pub name: Cow<'a, str>,
+    pub in_pin: Cow<'a, str>,
+    pub out_pin: Cow<'a, str>,
+    pub bits: Option<i32>, // max bits
+}
+
+// Keyword enums. Display and FromStr use the keyword exactly as written in DEF.
+macro_rules! def_keyword {
+    ($name:ident { $($variant:ident => $keyword:expr),+ $(,)? }) => {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum $name {
+            $($variant),+
+        }
+
+        impl $name {
+            // every keyword of this enum in DEF syntax
+            pub const KEYWORDS: &'static [&'static str] = &[$($keyword),+];
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str(match self {
+                    $($name::$variant => $keyword),+
+                })
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = UnknownKeyword;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s {
+                    $($keyword => Ok($name::$variant),)+
+                    _ => Err(UnknownKeyword(s.to_string())),
+                }
+            }
+        }
+
+        // serialized as the DEF keyword, ie. "FIXED"
+        #[cfg(feature = "serde")]
+        impl serde::Serialize for $name {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl<'de> serde::Deserialize<'de> for $name {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                String::deserialize(deserializer)?
+                    .parse()
+                    .map_err(serde::de::Error::custom)
+            }
+        }
+    };
+}
+
+// Returned by FromStr of keyword enums for a word that is not one of its keywords
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownKeyword(pub String);
+
+impl fmt::Display for UnknownKeyword {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unknown keyword `{}`", self.0)
+    }
+}
+
+impl Error for UnknownKeyword {}
+
+def_keyword!(Orient {
+    N => "N",
+    W => "W",
+    S => "S",
+    E => "E",
+    FN => "FN",
+    FW => "FW",
+    FS => "FS",
+    FE => "FE",
+});
+
+// placement status of COMPONENTS, PINS and VPIN
+def_keyword!(PlacementStatus {
+    Fixed => "FIXED",
+    Cover => "COVER",
+    Placed => "PLACED",
+    Unplaced => "UNPLACED",
+});
+
+// routing status of regular and special wiring
+def_keyword!(RoutingStatus {
+    Cover => "COVER",
+    Fixed => "FIXED",
+    Routed => "ROUTED",
+    NoShield => "NOSHIELD",
+    Shield => "SHIELD",
+});
+
+def_keyword!(Source {
+    Dist => "DIST",
+    Netlist => "NETLIST",
+    Timing => "TIMING",
+    User => "USER",
+    Test => "TEST",
+});
+
+// USE of NETS, SPECIALNETS and PINS
+def_keyword!(NetUse {
+    Analog => "ANALOG",
+    Clock => "CLOCK",
+    Ground => "GROUND",
+    Power => "POWER",
+    Reset => "RESET",
+    Scan => "SCAN",
+    Signal => "SIGNAL",
+    Tieoff => "TIEOFF",
+});
+
+def_keyword!(Pattern {
+    Balanced => "BALANCED",
+    Steiner => "STEINER",
+    Trunk => "TRUNK",
+    WiredLogic => "WIREDLOGIC",
+});
+
+// SHAPE of special wiring
+def_keyword!(ShapeType {
+    Ring => "RING",
+    PadRing => "PADRING",
+    BlockRing => "BLOCKRING",
+    Stripe => "STRIPE",
+    FollowPin => "FOLLOWPIN",
+    IoWire => "IOWIRE",
+    CoreWire => "COREWIRE",
+    BlockWire => "BLOCKWIRE",
+    BlockageWire => "BLOCKAGEWIRE",
+    FillWire => "FILLWIRE",
+    FillWireOpc => "FILLWIREOPC",
+    DrcFill => "DRCFILL",
+});
+
+def_keyword!(Direction {
+    Input => "INPUT",
+    Output => "OUTPUT",
+    Inout => "INOUT",
+    Feedthru => "FEEDTHRU",
+});
+
+def_keyword!(RegionType {
+    Fence => "FENCE",
+    Guide => "GUIDE",
+});
+
+def_keyword!(AntennaModel {
+    Oxide1 => "OXIDE1",
+    Oxide2 => "OXIDE2",
+    Oxide3 => "OXIDE3",
+    Oxide4 => "OXIDE4",
+});
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use crate::collection::def_parser::parse_def;
+    use crate::collection::def_types::*;
+    use std::io::Read;
+
+    #[test]
+    fn test_json() {
+        let data = "DESIGN test ;
+COMPONENTS 1 ;
+- I1 INV + SOURCE DIST + PLACED ( 10 20 ) FN + PROPERTY weight 2 ;
+END COMPONENTS
+END DESIGN
+";
+        let def = parse_def(data).unwrap();
+        assert_eq!(
+            serde_json::to_string(&def.components[0]).unwrap(),
+            "{\"name\":\"I1\",\"model\":\"INV\",\"eeqmaster\":null,\"source\":\"DIST\",\
+             \"placement\":{\"status\":\"PLACED\",\"location\":[[10,20],\"FN\"]},\
+             \"weight\":null,\"region\":null,\"halo\":null,\"route_halo\":null,\
+             \"properties\":[[\"weight\",{\"IValue\":2}]]}"
+        );
+
+        let mut input_def = std::fs::File::open("tests/def_test.def").unwrap();
+        let mut data = String::new();
+        input_def.read_to_string(&mut data).unwrap();
+        let def = parse_def(&data).unwrap();
+        let json = serde_json::to_string(&def).unwrap();
+        assert_eq!(serde_json::from_str::<Def>(&json).unwrap(), def);
+        assert!(serde_json::from_str::<Orient>("\"NE\"").is_err());
+    }
+}
@@ -0,0 +1,1140 @@
+use std::borrow::Cow;
+use std::io::{Result, Write};
+
+// def
+use super::def_types::{
+    Blockage, Component, Def, DesignConfig, Extension, Fill, Geometry, Group, LayerRule, Ndr, Net,
+    Pin, PinAntenna, PinPort, Pinprop, Port, PortElem, PropValue, Properties, Region,
+    RegularWireStmt, RouteBody, RouteElem, RoutingStatus, RtPt, SNet, ScanChain, ShapeType, Slot,
+    SpecialWireStmt, Style, SubNetPin, Via, ViaBody,
+};
+
+// Write the whole DEF file. Sections are emitted in the order of the lef/def reference
+// and empty sections are skipped.
+pub fn write_def<W: Write>(w: &mut W, def: &Def) -> Result<()> {
+    write_header(
+        w,
+        def.version,
+        def.divider_char.as_deref(),
+        def.busbit_chars.as_deref(),
+    )?;
+    write_design_config(w, &def.config)?;
+    if !def.vias.is_empty() {
+        write_via_section(w, &def.vias)?;
+    }
+    if !def.styles.is_empty() {
+        write_style_section(w, &def.styles)?;
+    }
+    if !def.ndrs.is_empty() {
+        write_ndr_section(w, &def.ndrs)?;
+    }
+    if !def.regions.is_empty() {
+        write_region_section(w, &def.regions)?;
+    }
+    if let Some(layers) = &def.component_mask_shift {
+        write_maskshift_section(w, layers)?;
+    }
+    if !def.components.is_empty() {
+        write_component_section(w, &def.components)?;
+    }
+    if !def.pins.is_empty() {
+        write_pin_section(w, &def.pins)?;
+    }
+    if !def.pin_properties.is_empty() {
+        write_pinproperty_section(w, &def.pin_properties)?;
+    }
+    if !def.blockages.is_empty() {
+        write_blockage_section(w, &def.blockages)?;
+    }
+    if !def.slots.is_empty() {
+        write_slot_section(w, &def.slots)?;
+    }
+    if !def.fills.is_empty() {
+        write_fill_section(w, &def.fills)?;
+    }
+    if !def.special_nets.is_empty() {
+        write_snet_section(w, &def.special_nets)?;
+    }
+    if !def.nets.is_empty() {
+        write_net_section(w, &def.nets)?;
+    }
+    if !def.scan_chains.is_empty() {
+        write_scanchain_section(w, &def.scan_chains)?;
+    }
+    if !def.groups.is_empty() {
+        write_group_section(w, &def.groups)?;
+    }
+    if !def.extensions.is_empty() {
+        write_extension_section(w, &def.extensions)?;
+    }
+    writeln!(w, "END DESIGN")
+}
+
+// Write the whole DEF file into a String
+pub fn def_to_string(def: &Def) -> String {
+    let mut out = Vec::new();
+    write_def(&mut out, def).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+pub fn write_header<W: Write>(
+    w: &mut W,
+    version: Option<f64>,
+    divider_char: Option<&str>,
+    busbit_chars: Option<&str>,
+) -> Result<()> {
+    if let Some(n) = version {
+        writeln!(w, "VERSION {} ;", real(n))?;
+    }
+    if let Some(n) = divider_char {
+        writeln!(w, "DIVIDERCHAR \"{}\" ;", n)?;
+    }
+    if let Some(n) = busbit_chars {
+        writeln!(w, "BUSBITCHARS \"{}\" ;", n)?;
+    }
+    Ok(())
+}
+
+pub fn write_design_config<W: Write>(w: &mut W, config: &DesignConfig) -> Result<()> {
+    writeln!(w, "DESIGN {} ;", config.design_name)?;
+    if let Some(n) = &config.technology {
+        writeln!(w, "TECHNOLOGY {} ;", n)?;
+    }
+    if let Some(n) = config.units {
+        writeln!(w, "UNITS DISTANCE MICRONS {} ;", n)?;
+    }
+    for n in &config.history {
+        writeln!(w, "HISTORY {} ;", n)?;
+    }
+    if let Some(prop_defs) = &config.prop_defs {
+        writeln!(w, "PROPERTYDEFINITIONS")?;
+        for p in prop_defs {
+            write!(w, "  {} {}", p.object_type, p.name)?;
+            if let Some((range, value)) = p.int_value {
+                write!(w, " INTEGER")?;
+                if let Some((min, max)) = range {
+                    write!(w, " RANGE {} {}", min, max)?;
+                }
+                if let Some(n) = value {
+                    write!(w, " {}", n)?;
+                }
+            } else if let Some((range, value)) = p.real_value {
+                write!(w, " REAL")?;
+                if let Some((min, max)) = range {
+                    write!(w, " RANGE {} {}", real(min), real(max))?;
+                }
+                if let Some(n) = value {
+                    write!(w, " {}", real(n))?;
+                }
+            } else {
+                write!(w, " STRING")?;
+                if let Some(n) = &p.string_value {
+                    write!(w, " \"{}\"", n)?;
+                }
+            }
+            writeln!(w, " ;")?;
+        }
+        writeln!(w, "END PROPERTYDEFINITIONS")?;
+    }
+    if let Some(pts) = &config.die_area {
+        write!(w, "DIEAREA")?;
+        write_pts(w, pts)?;
+        writeln!(w, " ;")?;
+    }
+    for row in config.rows.iter().flatten() {
+        write!(
+            w,
+            "ROW {} {} {} {} {} DO {} BY {} STEP {} {}",
+            row.name,
+            row.site,
+            row.x,
+            row.y,
+            row.orient,
+            row.num_x,
+            row.num_y,
+            row.step_x,
+            row.step_y
+        )?;
+        if let Some(properties) = &row.properties {
+            write_properties(w, properties)?;
+        }
+        writeln!(w, " ;")?;
+    }
+    for track in config.tracks.iter().flatten() {
+        write!(
+            w,
+            "TRACKS {} {} DO {} STEP {}",
+            track.axis, track.start, track.num, track.step
+        )?;
+        if let Some(layers) = &track.layers {
+            write!(w, " LAYER {}", layers.join(" "))?;
+        }
+        writeln!(w, " ;")?;
+    }
+    for grid in config.gcell_grids.iter().flatten() {
+        writeln!(
+            w,
+            "GCELLGRID {} {} DO {} STEP {} ;",
+            grid.axis, grid.start, grid.num, grid.step
+        )?;
+    }
+    Ok(())
+}
+
+pub fn write_via_section<W: Write>(w: &mut W, vias: &[Via]) -> Result<()> {
+    writeln!(w, "VIAS {} ;", vias.len())?;
+    for via in vias {
+        write!(w, "- {}", via.name)?;
+        match &via.body {
+            ViaBody::Generated(n) => {
+                write!(w, "\n  + VIARULE {}", n.via_rule)?;
+                write!(w, "\n  + CUTSIZE {} {}", n.cut_size.0, n.cut_size.1)?;
+                write!(
+                    w,
+                    "\n  + LAYERS {} {} {}",
+                    n.bot_layer, n.cut_layer, n.top_layer
+                )?;
+                write!(
+                    w,
+                    "\n  + CUTSPACING {} {}",
+                    n.cut_spacing.0, n.cut_spacing.1
+                )?;
+                let (a, b, c, d) = n.enclosure;
+                write!(w, "\n  + ENCLOSURE {} {} {} {}", a, b, c, d)?;
+                if let Some((rows, cols)) = n.row_col {
+                    write!(w, "\n  + ROWCOL {} {}", rows, cols)?;
+                }
+                if let Some((x, y)) = n.origin {
+                    write!(w, "\n  + ORIGIN {} {}", x, y)?;
+                }
+                if let Some((a, b, c, d)) = n.offset {
+                    write!(w, "\n  + OFFSET {} {} {} {}", a, b, c, d)?;
+                }
+                if let Some(pattern) = &n.pattern {
+                    write!(w, "\n  + PATTERN {}", pattern)?;
+                }
+            }
+            ViaBody::Fixed(shapes) => {
+                for (layer, mask, shape) in shapes {
+                    let keyword = match shape {
+                        Geometry::Rect(_) => "RECT",
+                        Geometry::Polygon(_) => "POLYGON",
+                    };
+                    write!(w, "\n  + {} {}", keyword, layer)?;
+                    if let Some(n) = mask {
+                        write!(w, " + MASK {}", n)?;
+                    }
+                    match shape {
+                        Geometry::Rect(rect) => write_rect(w, rect)?,
+                        Geometry::Polygon(pts) => write_pts(w, pts)?,
+                    }
+                }
+            }
+        }
+        writeln!(w, " ;")?;
+    }
+    writeln!(w, "END VIAS")
+}
+
+pub fn write_style_section<W: Write>(w: &mut W, styles: &[Style]) -> Result<()> {
+    writeln!(w, "STYLES {} ;", styles.len())?;
+    for style in styles {
+        write!(w, "- STYLE {}", style.num)?;
+        write_pts(w, &style.points)?;
+        writeln!(w, " ;")?;
+    }
+    writeln!(w, "END STYLES")
+}
+
+pub fn write_ndr_section<W: Write>(w: &mut W, ndrs: &[Ndr]) -> Result<()> {
+    writeln!(w, "NONDEFAULTRULES {} ;", ndrs.len())?;
+    for ndr in ndrs {
+        write!(w, "- {}", ndr.name)?;
+        if ndr.hard_spacing {
+            write!(w, "\n  + HARDSPACING")?;
+        }
+        for layer in &ndr.layers {
+            write!(w, "\n  + LAYER {} WIDTH {}", layer.name, layer.width)?;
+            if let Some(n) = layer.diag_width {
+                write!(w, " DIAGWIDTH {}", n)?;
+            }
+            if let Some(n) = layer.spacing {
+                write!(w, " SPACING {}", n)?;
+            }
+            if let Some(n) = layer.wire_ext {
+                write!(w, " WIREEXT {}", n)?;
+            }
+        }
+        for via in &ndr.vias {
+            write!(w, "\n  + VIA {}", via)?;
+        }
+        for via_rule in &ndr.via_rules {
+            write!(w, "\n  + VIARULE {}", via_rule)?;
+        }
+        for (layer, n) in &ndr.min_cuts {
+            write!(w, "\n  + MINCUTS {} {}", layer, n)?;
+        }
+        write_properties(w, &ndr.properties)?;
+        writeln!(w, " ;")?;
+    }
+    writeln!(w, "END NONDEFAULTRULES")
+}
+
+pub fn write_region_section<W: Write>(w: &mut W, regions: &[Region]) -> Result<()> {
+    writeln!(w, "REGIONS {} ;", regions.len())?;
+    for region in regions {
+        write!(w, "- {}", region.name)?;
+        for rect in &region.rects {
+            write_rect(w, rect)?;
+        }
+        if let Some(n) = region.region_type {
+            write!(w, "\n  + TYPE {}", n)?;
+        }
+        write_properties(w, &region.properties)?;
+        writeln!(w, " ;")?;
+    }
+    writeln!(w, "END REGIONS")
+}
+
+pub fn write_maskshift_section<W: Write>(w: &mut W, layers: &[Cow<str>]) -> Result<()> {
+    writeln!(w, "COMPONENTMASKSHIFT {} ;", layers.join(" "))
+}
+
+pub fn write_component_section<W: Write>(w: &mut W, components: &[Component]) -> Result<()> {
+    writeln!(w, "COMPONENTS {} ;", components.len())?;
+    for comp in components {
+        write!(w, "- {} {}", comp.name, comp.model)?;
+        if let Some(n) = &comp.eeqmaster {
+            write!(w, "\n  + EEQMASTER {}", n)?;
+        }
+        if let Some(n) = comp.source {
+            write!(w, "\n  + SOURCE {}", n)?;
+        }
+        write!(w, "\n  + {}", comp.placement.status)?;
+        if let Some(((x, y), orient)) = comp.placement.location {
+            write!(w, " ( {} {} ) {}", x, y, orient)?;
+        }
+        if let Some(n) = comp.weight {
+            write!(w, "\n  + WEIGHT {}", n)?;
+        }
+        if let Some(n) = &comp.region {
+            write!(w, "\n  + REGION {}", n)?;
+        }
+        if let Some(n) = &comp.halo {
+            write!(w, "\n  + HALO")?;
+            if n.soft {
+                write!(w, " SOFT")?;
+            }
+            write!(w, " {} {} {} {}", n.left, n.bottom, n.right, n.top)?;
+        }
+        if let Some(n) = &comp.route_halo {
+            write!(
+                w,
+                "\n  + ROUTEHALO {} {} {}",
+                n.dist, n.min_layer, n.max_layer
+            )?;
+        }
+        write_properties(w, &comp.properties)?;
+        writeln!(w, " ;")?;
+    }
+    writeln!(w, "END COMPONENTS")
+}
+
+pub fn write_pin_section<W: Write>(w: &mut W, pins: &[Pin]) -> Result<()> {
+    writeln!(w, "PINS {} ;", pins.len())?;
+    for pin in pins {
+        write!(w, "- {} + NET {}", pin.name, pin.net)?;
+        if pin.special {
+            write!(w, "\n  + SPECIAL")?;
+        }
+        if let Some(n) = pin.direction {
+            write!(w, "\n  + DIRECTION {}", n)?;
+        }
+        if let Some(n) = &pin.net_expr {
+            write!(w, "\n  + NETEXPR \"{}\"", n)?;
+        }
+        if let Some(n) = &pin.supply_sensitivity {
+            write!(w, "\n  + SUPPLYSENSITIVITY {}", n)?;
+        }
+        if let Some(n) = &pin.ground_sensitivity {
+            write!(w, "\n  + GROUNDSENSITIVITY {}", n)?;
+        }
+        if let Some(n) = pin.use_mode {
+            write!(w, "\n  + USE {}", n)?;
+        }
+        for antenna in &pin.antenna {
+            write_pin_antenna(w, antenna)?;
+        }
+        match &pin.port {
+            Port::SinglePort(port) => write_pin_port(w, port, "  ")?,
+            Port::ManyPorts(ports) => {
+                for port in ports {
+                    write!(w, "\n  + PORT")?;
+                    write_pin_port(w, port, "    ")?;
+                }
+            }
+        }
+        writeln!(w, " ;")?;
+    }
+    writeln!(w, "END PINS")
+}
+
+fn write_pin_antenna<W: Write>(w: &mut W, antenna: &PinAntenna) -> Result<()> {
+    let (keyword, value, layer) = match antenna {
+        PinAntenna::Model(n) => return write!(w, "\n  + ANTENNAMODEL {}", n),
+        PinAntenna::PartialMetalArea(n, layer) => {
+            ("ANTENNAPINPARTIALMETALAREA", n, layer.as_deref())
+        }
+        PinAntenna::PartialMetalSideArea(n, layer) => {
+            ("ANTENNAPINPARTIALMETALSIDEAREA", n, layer.as_deref())
+        }
+        PinAntenna::PartialCutArea(n, layer) => ("ANTENNAPINPARTIALCUTAREA", n, layer.as_deref()),
+        PinAntenna::DiffArea(n, layer) => ("ANTENNAPINDIFFAREA", n, layer.as_deref()),
+        PinAntenna::GateArea(n, layer) => ("ANTENNAPINGATEAREA", n, layer.as_deref()),
+        PinAntenna::MaxAreaCar(n, layer) => ("ANTENNAPINMAXAREACAR", n, Some(&**layer)),
+        PinAntenna::MaxSideAreaCar(n, layer) => ("ANTENNAPINMAXSIDEAREACAR", n, Some(&**layer)),
+        PinAntenna::MaxCutCar(n, layer) => ("ANTENNAPINMAXCUTCAR", n, Some(&**layer)),
+    };
+    write!(w, "\n  + {} {}", keyword, value)?;
+    if let Some(n) = layer {
+        write!(w, " LAYER {}", n)?;
+    }
+    Ok(())
+}
+
+fn write_pin_port<W: Write>(w: &mut W, port: &PinPort, indent: &str) -> Result<()> {
+    for elem in &port.elems {
+        match elem {
+            PortElem::Layer {
+                layer,
+                mask,
+                rule,
+                rect,
+            } => {
+                write!(w, "\n{}+ LAYER {}", indent, layer)?;
+                if let Some(n) = mask {
+                    write!(w, " MASK {}", n)?;
+                }
+                if let Some(n) = rule {
+                    write_layer_rule(w, "", n)?;
+                }
+                write_rect(w, rect)?;
+            }
+            PortElem::Polygon {
+                layer,
+                mask,
+                rule,
+                points,
+            } => {
+                write!(w, "\n{}+ POLYGON {}", indent, layer)?;
+                if let Some(n) = mask {
+                    write!(w, " MASK {}", n)?;
+                }
+                if let Some(n) = rule {
+                    write_layer_rule(w, "", n)?;
+                }
+                write_pts(w, points)?;
+            }
+            PortElem::Via { via, mask, point } => {
+                write!(w, "\n{}+ VIA {}", indent, via)?;
+                if let Some(n) = mask {
+                    write!(w, " MASK {:03}", n)?;
+                }
+                write!(w, " ( {} {} )", point.0, point.1)?;
+            }
+        }
+    }
+    let location = &port.location;
+    write!(
+        w,
+        "\n{}+ {} ( {} {} ) {}",
+        indent, location.status, location.point.0, location.point.1, location.orient
+    )
+}
+
+pub fn write_pinproperty_section<W: Write>(w: &mut W, pinprops: &[Pinprop]) -> Result<()> {
+    writeln!(w, "PINPROPERTIES {} ;", pinprops.len())?;
+    for pinprop in pinprops {
+        write!(
+            w,
+            "- {} {}",
+            pinprop.component.as_deref().unwrap_or("PIN"),
+            pinprop.pin
+        )?;
+        write_properties(w, &pinprop.properties)?;
+        writeln!(w, " ;")?;
+    }
+    writeln!(w, "END PINPROPERTIES")
+}
+
+pub fn write_blockage_section<W: Write>(w: &mut W, blockages: &[Blockage]) -> Result<()> {
+    writeln!(w, "BLOCKAGES {} ;", blockages.len())?;
+    for blockage in blockages {
+        match blockage {
+            Blockage::Layer {
+                layer,
+                slots,
+                pushdown,
+                fills,
+                except_pg_net,
+                rule,
+                component,
+                mask,
+                shapes,
+            } => {
+                write!(w, "- LAYER {}", layer)?;
+                if *slots {
+                    write!(w, " + SLOTS")?;
+                }
+                if *pushdown {
+                    write!(w, " + PUSHDOWN")?;
+                }
+                if *fills {
+                    write!(w, " + FILLS")?;
+                }
+                if *except_pg_net {
+                    write!(w, " + EXCEPTPGNET")?;
+                }
+                if let Some(n) = rule {
+                    write_layer_rule(w, " +", n)?;
+                }
+                if let Some(n) = component {
+                    write!(w, " + COMPONENT {}", n)?;
+                }
+                if let Some(n) = mask {
+                    write!(w, " + MASK {}", n)?;
+                }
+                write_shapes(w, shapes)?;
+            }
+            Blockage::Placement {
+                soft,
+                partial,
+                pushdown,
+                component,
+                shapes,
+            } => {
+                write!(w, "- PLACEMENT")?;
+                if *soft {
+                    write!(w, " + SOFT")?;
+                }
+                if let Some(n) = partial {
+                    write!(w, " + PARTIAL {}", real(*n))?;
+                }
+                if *pushdown {
+                    write!(w, " + PUSHDOWN")?;
+                }
+                if let Some(n) = component {
+                    write!(w, " + COMPONENT {}", n)?;
+                }
+                write_shapes(w, shapes)?;
+            }
+        }
+        writeln!(w, " ;")?;
+    }
+    writeln!(w, "END BLOCKAGES")
+}
+
+pub fn write_slot_section<W: Write>(w: &mut W, slots: &[Slot]) -> Result<()> {
+    writeln!(w, "SLOTS {} ;", slots.len())?;
+    for slot in slots {
+        write!(w, "- LAYER {}", slot.layer)?;
+        write_shapes(w, &slot.shapes)?;
+        writeln!(w, " ;")?;
+    }
+    writeln!(w, "END SLOTS")
+}
+
+pub fn write_fill_section<W: Write>(w: &mut W, fills: &[Fill]) -> Result<()> {
+    writeln!(w, "FILLS {} ;", fills.len())?;
+    for fill in fills {
+        match fill {
+            Fill::Layer {
+                layer,
+                mask,
+                opc,
+                shapes,
+            } => {
+                write!(w, "- LAYER {}", layer)?;
+                if let Some(n) = mask {
+                    write!(w, " + MASK {}", n)?;
+                }
+                if *opc {
+                    write!(w, " + OPC")?;
+                }
+                write_shapes(w, shapes)?;
+            }
+            Fill::Via {
+                via,
+                mask,
+                opc,
+                points,
+            } => {
+                write!(w, "- VIA {}", via)?;
+                if let Some(n) = mask {
+                    write!(w, " + MASK {:03}", n)?;
+                }
+                if *opc {
+                    write!(w, " + OPC")?;
+                }
+                write_pts(w, points)?;
+            }
+        }
+        writeln!(w, " ;")?;
+    }
+    writeln!(w, "END FILLS")
+}
+
+pub fn write_snet_section<W: Write>(w: &mut W, snets: &[SNet]) -> Result<()> {
+    writeln!(w, "SPECIALNETS {} ;", snets.len())?;
+    for snet in snets {
+        write!(w, "- {}", snet.name)?;
+        for conn in &snet.connections {
+            write!(
+                w,
+                " ( {} {}",
+                conn.component.as_deref().unwrap_or("PIN"),
+                conn.pin
+            )?;
+            if conn.synthesized {
+                write!(w, " + SYNTHESIZED")?;
+            }
+            write!(w, " )")?;
+        }
+        for (layer, width) in &snet.widths {
+            write!(w, "\n  + WIDTH {} {}", layer, width)?;
+        }
+        if let Some(n) = snet.voltage {
+            write!(w, "\n  + VOLTAGE {}", n)?;
+        }
+        for stmt in &snet.wiring {
+            match stmt {
+                SpecialWireStmt::Polygon {
+                    status,
+                    shape,
+                    mask,
+                    layer,
+                    points,
+                } => {
+                    write!(w, "\n ")?;
+                    write_special_shape_prefix(w, status, shape, mask)?;
+                    write!(w, " + POLYGON {}", layer)?;
+                    write_pts(w, points)?;
+                }
+                SpecialWireStmt::Rect {
+                    status,
+                    shape,
+                    mask,
+                    layer,
+                    rect,
+                } => {
+                    write!(w, "\n ")?;
+                    write_special_shape_prefix(w, status, shape, mask)?;
+                    write!(w, " + RECT {}", layer)?;
+                    write_rect(w, rect)?;
+                }
+                SpecialWireStmt::Via {
+                    status,
+                    shape,
+                    mask,
+                    via,
+                    orient,
+                    points,
+                } => {
+                    write!(w, "\n ")?;
+                    write_special_shape_prefix(w, status, shape, &None)?;
+                    if let Some(n) = mask {
+                        write!(w, " + MASK {:03}", n)?;
+                    }
+                    write!(w, " + VIA {}", via)?;
+                    if let Some(n) = orient {
+                        write!(w, " {}", n)?;
+                    }
+                    write_pts(w, points)?;
+                }
+                SpecialWireStmt::Route {
+                    status,
+                    shield_net,
+                    wires,
+                } => {
+                    write!(w, "\n  + {}", status)?;
+                    if let Some(n) = shield_net {
+                        write!(w, " {}", n)?;
+                    }
+                    for (i, wire) in wires.iter().enumerate() {
+                        if i > 0 {
+                            write!(w, "\n    NEW")?;
+                        }
+                        write!(w, " {} {}", wire.layer, wire.width)?;
+                        if let Some(n) = wire.shape {
+                            write!(w, " + SHAPE {}", n)?;
+                        }
+                        if let Some(n) = wire.style {
+                            write!(w, " + STYLE {}", n)?;
+                        }
+                        write_route_body(w, &wire.route)?;
+                    }
+                }
+            }
+        }
+        if let Some(n) = snet.source {
+            write!(w, "\n  + SOURCE {}", n)?;
+        }
+        if snet.fixed_bump {
+            write!(w, "\n  + FIXEDBUMP")?;
+        }
+        if let Some(n) = &snet.original {
+            write!(w, "\n  + ORIGINAL {}", n)?;
+        }
+        if let Some(n) = snet.use_mode {
+            write!(w, "\n  + USE {}", n)?;
+        }
+        if let Some(n) = snet.pattern {
+            write!(w, "\n  + PATTERN {}", n)?;
+        }
+        if let Some(n) = snet.est_cap {
+            write!(w, "\n  + ESTCAP {}", n)?;
+        }
+        if let Some(n) = snet.weight {
+            write!(w, "\n  + WEIGHT {}", n)?;
+        }
+        write_properties(w, &snet.properties)?;
+        writeln!(w, " ;")?;
+    }
+    writeln!(w, "END SPECIALNETS")
+}
+
+pub fn write_net_section<W: Write>(w: &mut W, nets: &[Net]) -> Result<()> {
+    writeln!(w, "NETS {} ;", nets.len())?;
+    for net in nets {
+        write!(w, "- {}", net.name)?;
+        for conn in &net.connections {
+            write!(
+                w,
+                " ( {} {}",
+                conn.component.as_deref().unwrap_or("PIN"),
+                conn.pin
+            )?;
+            if conn.synthesized {
+                write!(w, " + SYNTHESIZED")?;
+            }
+            write!(w, " )")?;
+        }
+        for n in &net.shield_nets {
+            write!(w, "\n  + SHIELDNET {}", n)?;
+        }
+        for vpin in &net.vpins {
+            write!(w, "\n  + VPIN {} LAYER {}", vpin.name, vpin.layer)?;
+            write_rect(w, &vpin.rect)?;
+            let location = &vpin.location;
+            write!(
+                w,
+                " {} ( {} {} ) {}",
+                location.status, location.point.0, location.point.1, location.orient
+            )?;
+        }
+        for subnet in &net.subnets {
+            write!(w, "\n  + SUBNET {}", subnet.name)?;
+            for pin in &subnet.pins {
+                match pin {
+                    SubNetPin::Component(comp, pin) => write!(w, " ( {} {} )", comp, pin)?,
+                    SubNetPin::Pin(pin) => write!(w, " ( PIN {} )", pin)?,
+                    SubNetPin::Vpin(pin) => write!(w, " ( VPIN {} )", pin)?,
+                }
+            }
+            if let Some(n) = &subnet.ndr {
+                write!(w, "\n    NONDEFAULTRULE {}", n)?;
+            }
+            for stmt in &subnet.wiring {
+                write!(w, "\n    ")?;
+                write_regular_wiring(w, stmt)?;
+            }
+        }
+        if let Some(n) = net.xtalk {
+            write!(w, "\n  + XTALK {}", n)?;
+        }
+        if let Some(n) = &net.ndr {
+            write!(w, "\n  + NONDEFAULTRULE {}", n)?;
+        }
+        for stmt in &net.wiring {
+            write!(w, "\n  + ")?;
+            write_regular_wiring(w, stmt)?;
+        }
+        if let Some(n) = net.source {
+            write!(w, "\n  + SOURCE {}", n)?;
+        }
+        if net.fixed_bump {
+            write!(w, "\n  + FIXEDBUMP")?;
+        }
+        if let Some(n) = net.frequency {
+            write!(w, "\n  + FREQUENCY {}", real(n))?;
+        }
+        if let Some(n) = &net.original {
+            write!(w, "\n  + ORIGINAL {}", n)?;
+        }
+        if let Some(n) = net.use_mode {
+            write!(w, "\n  + USE {}", n)?;
+        }
+        if let Some(n) = net.pattern {
+            write!(w, "\n  + PATTERN {}", n)?;
+        }
+        if let Some(n) = net.est_cap {
+            write!(w, "\n  + ESTCAP {}", n)?;
+        }
+        if let Some(n) = net.weight {
+            write!(w, "\n  + WEIGHT {}", n)?;
+        }
+        write_properties(w, &net.properties)?;
+        writeln!(w, " ;")?;
+    }
+    writeln!(w, "END NETS")
+}
+
+fn write_regular_wiring<W: Write>(w: &mut W, stmt: &RegularWireStmt) -> Result<()> {
+    write!(w, "{}", stmt.status)?;
+    for (i, wire) in stmt.wires.iter().enumerate() {
+        if i > 0 {
+            write!(w, "\n    NEW")?;
+        }
+        write!(w, " {}", wire.layer)?;
+        if let Some(n) = &wire.taper_rule {
+            write!(w, " TAPERRULE {}", n)?;
+        }
+        if let Some(n) = wire.style {
+            write!(w, " STYLE {}", n)?;
+        }
+        write_route_body(w, &wire.route)?;
+    }
+    Ok(())
+}
+
+pub fn write_scanchain_section<W: Write>(w: &mut W, scanchains: &[ScanChain]) -> Result<()> {
+    writeln!(w, "SCANCHAINS {} ;", scanchains.len())?;
+    for chain in scanchains {
+        write!(w, "- {}", chain.name)?;
+        if let Some((name, maxbits)) = &chain.partition {
+            write!(w, "\n  + PARTITION {}", name)?;
+            if let Some(n) = maxbits {
+                write!(w, " MAXBITS {}", n)?;
+            }
+        }
+        if let Some((in_pin, out_pin)) = &chain.common_scan_pins {
+            write!(
+                w,
+                "\n  + COMMONSCANPINS ( IN {} ) ( OUT {} )",
+                in_pin, out_pin
+            )?;
+        }
+        if let Some((comp, pin)) = &chain.start {
+            write!(
+                w,
+                "\n  + START {} {}",
+                comp.as_deref().unwrap_or("PIN"),
+                pin
+            )?;
+        }
+        for (keyword, cell) in [("FLOATING", &chain.floating), ("ORDERED", &chain.ordered)].iter() {
+            if let Some(cell) = cell {
+                write!(
+                    w,
+                    "\n  + {} {} ( IN {} ) ( OUT {} )",
+                    keyword, cell.name, cell.in_pin, cell.out_pin
+                )?;
+                if let Some(n) = cell.bits {
+                    write!(w, " ( BITS {} )", n)?;
+                }
+            }
+        }
+        if let Some((comp, pin)) = &chain.stop {
+            write!(w, "\n  + STOP {} {}", comp.as_deref().unwrap_or("PIN"), pin)?;
+        }
+        writeln!(w, " ;")?;
+    }
+    writeln!(w, "END SCANCHAINS")
+}
+
+pub fn write_group_section<W: Write>(w: &mut W, groups: &[Group]) -> Result<()> {
+    writeln!(w, "GROUPS {} ;", groups.len())?;
+    for group in groups {
+        write!(w, "- {} {}", group.name, group.components.join(" "))?;
+        write!(w, "\n  + REGION {}", group.region)?;
+        write_properties(w, &group.properties)?;
+        writeln!(w, " ;")?;
+    }
+    writeln!(w, "END GROUPS")
+}
+
+// extensions are written back unchanged
+pub fn write_extension_section<W: Write>(w: &mut W, extensions: &[Extension]) -> Result<()> {
+    for extension in extensions {
+        writeln!(w, "BEGINEXT \"{}\"{}ENDEXT", extension.tag, extension.body)?;
+    }
+    Ok(())
+}
+
+fn write_properties<W: Write>(w: &mut W, properties: &Properties) -> Result<()> {
+    for (name, value) in properties {
+        match value {
+            PropValue::SValue(n) => write!(w, "\n  + PROPERTY {} \"{}\"", name, n)?,
+            PropValue::RValue(n) => write!(w, "\n  + PROPERTY {} {}", name, real(*n))?,
+            PropValue::IValue(n) => write!(w, "\n  + PROPERTY {} {}", name, n)?,
+        }
+    }
+    Ok(())
+}
+
+fn write_shapes<W: Write>(w: &mut W, shapes: &[Geometry]) -> Result<()> {
+    for shape in shapes {
+        match shape {
+            Geometry::Rect(rect) => {
+                write!(w, "\n    RECT")?;
+                write_rect(w, rect)?;
+            }
+            Geometry::Polygon(pts) => {
+                write!(w, "\n    POLYGON")?;
+                write_pts(w, pts)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// [+ status] [+ SHAPE shape] [+ MASK maskNum] before + POLYGON, + RECT and + VIA in SPECIALNETS
+fn write_special_shape_prefix<W: Write>(
+    w: &mut W,
+    status: &Option<(RoutingStatus, Option<Cow<str>>)>,
+    shape: &Option<ShapeType>,
+    mask: &Option<i32>,
+) -> Result<()> {
+    if let Some((status, net)) = status {
+        write!(w, " + {}", status)?;
+        if let Some(n) = net {
+            write!(w, " {}", n)?;
+        }
+    }
+    if let Some(n) = shape {
+        write!(w, " + SHAPE {}", n)?;
+    }
+    if let Some(n) = mask {
+        write!(w, " + MASK {}", n)?;
+    }
+    Ok(())
+}
+
+// via masks are written with 3 digits, ie. MASK 031
+fn write_route_body<W: Write>(w: &mut W, route: &RouteBody) -> Result<()> {
+    for elem in route {
+        match elem {
+            RouteElem::Pt(pt) => write_rtpt(w, pt)?,
+            RouteElem::Via {
+                point,
+                mask,
+                via,
+                orient,
+                array,
+            } => {
+                if let Some(n) = point {
+                    write_rtpt(w, n)?;
+                }
+                if let Some(n) = mask {
+                    write!(w, " MASK {:03}", n)?;
+                }
+                write!(w, " {}", via)?;
+                if let Some(n) = orient {
+                    write!(w, " {}", n)?;
+                }
+                if let Some((num_x, num_y, step_x, step_y)) = array {
+                    write!(w, " DO {} BY {} STEP {} {}", num_x, num_y, step_x, step_y)?;
+                }
+            }
+            RouteElem::Mask(n) => write!(w, " MASK {}", n)?,
+            RouteElem::Rect((a, b, c, d)) => write!(w, " RECT ( {} {} {} {} )", a, b, c, d)?,
+            RouteElem::Virtual((x, y)) => write!(w, " VIRTUAL ( {} {} )", x, y)?,
+        }
+    }
+    Ok(())
+}
+
+// written after sep, ie. " +" in BLOCKAGES
+fn write_layer_rule<W: Write>(w: &mut W, sep: &str, rule: &LayerRule) -> Result<()> {
+    match rule {
+        LayerRule::Spacing(n) => write!(w, "{} SPACING {}", sep, n),
+        LayerRule::DesignRuleWidth(n) => write!(w, "{} DESIGNRULEWIDTH {}", sep, n),
+    }
+}
+
+fn write_rtpt<W: Write>(w: &mut W, pt: &RtPt) -> Result<()> {
+    let coord = |n: Option<i32>| match n {
+        Some(n) => n.to_string(),
+        None => String::from("*"),
+    };
+    write!(w, " ( {} {}", coord(pt.0), coord(pt.1))?;
+    if let Some(n) = pt.2 {
+        write!(w, " {}", n)?;
+    }
+    write!(w, " )")
+}
+
+fn write_rect<W: Write>(w: &mut W, rect: &((i32, i32), (i32, i32))) -> Result<()> {
+    let ((x1, y1), (x2, y2)) = rect;
+    write!(w, " ( {} {} ) ( {} {} )", x1, y1, x2, y2)
+}
+
+fn write_pts<W: Write>(w: &mut W, pts: &[(i32, i32)]) -> Result<()> {
+    for (x, y) in pts {
+        write!(w, " ( {} {} )", x, y)?;
+    }
+    Ok(())
+}
+
+// real numbers always keep a decimal point so that they are read back as real
+fn real(n: f64) -> String {
+    format!("{:?}", n)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::collection::component_parser::component_section;
+    use crate::collection::def_parser::parse_def;
+    use crate::collection::def_writer::*;
+    use std::io::Read;
+
+    #[test]
+    fn test_def_round_trip() {
+        let mut input_def = std::fs::File::open("tests/def_test.def").unwrap();
+        let mut data = String::new();
+        input_def.read_to_string(&mut data).unwrap();
+        let def = parse_def(&data).unwrap();
+
+        let written = def_to_string(&def);
+        assert_eq!(parse_def(&written).unwrap(), def);
+        assert_eq!(def_to_string(&parse_def(&written).unwrap()), written);
+    }
+
+    #[test]
+    fn test_snet_round_trip() {
+        let mut input_def = std::fs::File::open("tests/snet_test.def").unwrap();
+        let mut data = String::from("DESIGN test ;\n");
+        input_def.read_to_string(&mut data).unwrap();
+        data.push_str("END DESIGN\n");
+        let def = parse_def(&data).unwrap();
+
+        let written = def_to_string(&def);
+        assert!(written.contains(" ( 500 500 ) M1_M2 ( * * ) M1_M2\n"));
+        assert_eq!(parse_def(&written).unwrap(), def);
+    }
+
+    #[test]
+    fn test_write_mask() {
+        let data = "DESIGN test ;
+PINS 1 ;
+- P0 + NET N0
+  + LAYER M1 MASK 2 ( 0 0 ) ( 10 10 )
+  + VIA V12 MASK 21 ( 5 5 )
+  + PLACED ( 100 0 ) N ;
+END PINS
+VIAS 1 ;
+- V12 + POLYGON M1 + MASK 1 ( -5 -5 ) ( 5 5 ) ( 5 -5 ) ;
+END VIAS
+BLOCKAGES 1 ;
+- LAYER M1 + MASK 2 RECT ( 0 0 ) ( 5 5 ) ;
+END BLOCKAGES
+FILLS 1 ;
+- VIA V12 + MASK 031 + OPC ( 5 5 ) ;
+END FILLS
+SPECIALNETS 1 ;
+- VDD
+  + SHIELD VSS + SHAPE RING + MASK 2 + POLYGON M1 ( 0 0 ) ( 0 10 ) ( 10 0 )
+  + MASK 1 + RECT M2 ( 0 0 ) ( 10 10 )
+  + MASK 31 + VIA V12 FS ( 5 5 ) ( 15 * )
+  + ROUTED M1 20 ( 10 0 ) MASK 3 ( 10 20 ) MASK 031 V12 ;
+END SPECIALNETS
+END DESIGN
+";
+        let def = parse_def(data).unwrap();
+        let written = def_to_string(&def);
+        assert!(written.contains("+ VIA V12 MASK 021 ( 5 5 )"));
+        assert!(written.contains("( 10 0 ) MASK 3 ( 10 20 ) MASK 031 V12"));
+        assert!(written.contains("+ MASK 031 + VIA V12 FS ( 5 5 ) ( 15 5 )"));
+        assert_eq!(parse_def(&written).unwrap(), def);
+    }
+
+    #[test]
+    fn test_write_prop_defs() {
+        let data = "DESIGN test ;
+PROPERTYDEFINITIONS
+  COMPONENT weight INTEGER RANGE 1 100 5 ;
+  REGION limit INTEGER RANGE 0 10 ;
+  NET cap REAL RANGE 0.5 2.5 1.5 ;
+  DESIGN scale REAL 0.25 ;
+  COMPONENTPIN note STRING ;
+  DESIGN owner STRING \"eco\" ;
+END PROPERTYDEFINITIONS
+END DESIGN
+";
+        let def = parse_def(data).unwrap();
+        let prop_defs = def.config.prop_defs.as_ref().unwrap();
+        assert_eq!(
+            (prop_defs[0].data_type, prop_defs[0].int_value),
+            ('I', Some((Some((1, 100)), Some(5))))
+        );
+        assert_eq!(prop_defs[1].int_value, Some((Some((0, 10)), None)));
+        assert_eq!(
+            (prop_defs[2].data_type, prop_defs[2].real_value),
+            ('R', Some((Some((0.5, 2.5)), Some(1.5))))
+        );
+        assert_eq!(prop_defs[4].string_value, None);
+
+        let written = def_to_string(&def);
+        assert!(written.contains("COMPONENT weight INTEGER RANGE 1 100 5 ;"));
+        assert!(written.contains("REGION limit INTEGER RANGE 0 10 ;"));
+        assert!(written.contains("NET cap REAL RANGE 0.5 2.5 1.5 ;"));
+        assert!(written.contains("COMPONENTPIN note STRING ;"));
+        assert_eq!(parse_def(&written).unwrap(), def);
+    }
+
+    #[test]
+    fn test_write_layer_rule() {
+        let data = "DESIGN test ;
+PINS 1 ;
+- P0 + NET N0
+  + LAYER M1 DESIGNRULEWIDTH 20 ( 0 0 ) ( 10 10 )
+  + POLYGON M2 SPACING 5 ( 0 0 ) ( 0 10 ) ( 10 0 )
+  + PLACED ( 100 0 ) N ;
+END PINS
+BLOCKAGES 2 ;
+- LAYER M1 + DESIGNRULEWIDTH 45 RECT ( 0 0 ) ( 5 5 ) ;
+- LAYER M2 + SPACING 3 RECT ( 0 0 ) ( 5 5 ) ;
+END BLOCKAGES
+END DESIGN
+";
+        let def = parse_def(data).unwrap();
+        let written = def_to_string(&def);
+        assert!(written.contains("+ LAYER M1 DESIGNRULEWIDTH 20 ( 0 0 ) ( 10 10 )"));
+        assert!(written.contains("+ POLYGON M2 SPACING 5"));
+        assert!(written.contains("- LAYER M1 + DESIGNRULEWIDTH 45"));
+        assert!(written.contains("- LAYER M2 + SPACING 3"));
+        assert_eq!(parse_def(&written).unwrap(), def);
+    }
+
+    #[test]
+    fn test_write_component_section() {
+        let data = "COMPONENTS 2 ;
+- I1 B
+  + EEQMASTER A
+  + SOURCE NETLIST
+  + PLACED ( 100 100 ) N
+  + WEIGHT 100
+  + REGION region1
+  + HALO SOFT 5 6 7 8
+  + ROUTEHALO 100 METAL1 M3
+  + PROPERTY intprop 1 ;
+- I11 A
+  + UNPLACED ;
+END COMPONENTS
+";
+        let components = component_section(data).unwrap().1 .1;
+        let mut out = Vec::new();
+        write_component_section(&mut out, &components).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), data);
+    }
+}
@@ -1,32 +1,48 @@
+use std::borrow::Cow;
+
 use nom::branch::alt;
-use nom::bytes::complete::tag;
+use nom::bytes::complete::take_till;
+use nom::character::complete::multispace0;
 use nom::combinator::{map, opt};
-use nom::multi::many1;
+use nom::multi::{many0, many1};
 use nom::sequence::{delimited, preceded, terminated, tuple};
 use nom::IResult;
 
-use super::base::{float, number, tstring, ws};
+use super::base::{float, multispace_comment0, number, qstring, tag, tstring, ws};
 use super::common::{properties, pt_list, x_or_y};
 use super::def_types::{DesignConfig, GcellGrid, PropDef, Row, Track};
 use super::encoder::orient_encode;
 
-pub fn design_config(input: &str) -> IResult<&str, DesignConfig> {
-    tuple((
-        design_name,
-        opt(technology_name),
-        opt(units),
-        // HISTORY not supported
-        opt(prop_def_section),
-        opt(die_area),
-        opt(row_rule_def_list),
-        opt(tracks_rule_list),
-        opt(gcellgrid_list),
-    ))(input)
+pub fn design_config(input: &str) -> IResult<&str, DesignConfig<'_>> {
+    map(
+        tuple((
+            design_name,
+            opt(technology_name),
+            opt(units),
+            many0(history),
+            opt(prop_def_section),
+            opt(die_area),
+            opt(row_rule_def_list),
+            opt(tracks_rule_list),
+            opt(gcellgrid_list),
+        )),
+        |n| DesignConfig {
+            design_name: n.0.into(),
+            technology: n.1.map(Cow::from),
+            units: n.2,
+            history: n.3.into_iter().map(Cow::from).collect(),
+            prop_defs: n.4,
+            die_area: n.5,
+            rows: n.6,
+            tracks: n.7,
+            gcell_grids: n.8,
+        },
+    )(input)
 }
 
 // parse design name
 // Return: design name
-fn design_name(
+pub(crate) fn design_name(
     input: &str,
 ) -> IResult<
     &str,
@@ -36,7 +52,7 @@ fn design_name(
 }
 
 // parse technology name
-fn technology_name(
+pub(crate) fn technology_name(
     input: &str,
 ) -> IResult<
     &str,
@@ -46,7 +62,7 @@ fn technology_name(
 }
 
 // parse units
-fn units(
+pub(crate) fn units(
     input: &str,
 ) -> IResult<
     &str,
@@ -55,8 +71,17 @@ fn units(
     delimited(ws(tag("UNITS DISTANCE MICRONS")), number, ws(tag(";")))(input)
 }
 
+// parse history. The text is anything up to the next ";", a "#" in it is not a comment
+pub(crate) fn history(input: &str) -> IResult<&str, &str> {
+    delimited(
+        terminated(preceded(multispace_comment0, tag("HISTORY")), multispace0),
+        map(take_till(|c| c == ';'), str::trim_end),
+        ws(tag(";")),
+    )(input)
+}
+
 // parse die_area
-fn die_area(
+pub(crate) fn die_area(
     input: &str,
 ) -> IResult<
     &str,
@@ -66,34 +91,58 @@ fn die_area(
 }
 
 // parse property definitions
-fn prop_def_section(input: &str) -> IResult<&str, Vec<PropDef>> {
+pub(crate) fn prop_def_section(input: &str) -> IResult<&str, Vec<PropDef<'_>>> {
     delimited(
         ws(tag("PROPERTYDEFINITIONS")),
-        many1(tuple((
-            object_type,
-            tstring,
-            terminated(property_type_and_val, ws(tag(";"))),
-        ))),
+        many1(map(
+            tuple((
+                object_type,
+                tstring,
+                terminated(property_type_and_val, ws(tag(";"))),
+            )),
+            |n| PropDef {
+                object_type: n.0.into(),
+                name: n.1.into(),
+                data_type: (n.2).0,
+                string_value: (n.2).1.map(Cow::from),
+                int_value: (n.2).2,
+                real_value: (n.2).3,
+            },
+        )),
         ws(tag("END PROPERTYDEFINITIONS")),
     )(input)
 }
 
 // parse row rule definition
-fn row_rule_def_list(input: &str) -> IResult<&str, Vec<Row>> {
+pub(crate) fn row_rule_def_list(input: &str) -> IResult<&str, Vec<Row<'_>>> {
     many1(delimited(
         ws(tag("ROW")),
-        tuple((
-            tstring,
-            row_type,
-            number,
-            number,
-            orient_encode,
-            preceded(ws(tag("DO")), number),
-            preceded(ws(tag("BY")), number),
-            preceded(ws(tag("STEP")), number),
-            number,
-            opt(properties),
-        )),
+        map(
+            tuple((
+                tstring,
+                row_type,
+                number,
+                number,
+                orient_encode,
+                preceded(ws(tag("DO")), number),
+                preceded(ws(tag("BY")), number),
+                preceded(ws(tag("STEP")), number),
+                number,
+                opt(properties),
+            )),
+            |n| Row {
+                name: n.0.into(),
+                site: n.1.into(),
+                x: n.2,
+                y: n.3,
+                orient: n.4,
+                num_x: n.5,
+                num_y: n.6,
+                step_x: n.7,
+                step_y: n.8,
+                properties: n.9,
+            },
+        ),
         ws(tag(";")),
     ))(input)
 }
@@ -101,29 +150,46 @@ fn row_rule_def_list(input: &str) -> IResult<&str, Vec<Row>> {
 // parse gcell grid
 // Return:
 //    vector of (x_or_y, start,numColumns+1/numRows+1, steps)
-fn gcellgrid_list(input: &str) -> IResult<&str, Vec<GcellGrid>> {
+pub(crate) fn gcellgrid_list(input: &str) -> IResult<&str, Vec<GcellGrid>> {
     many1(delimited(
         ws(tag("GCELLGRID")),
-        tuple((
-            x_or_y,
-            number,
-            preceded(ws(tag("DO")), number),
-            preceded(ws(tag("STEP")), number),
-        )),
+        map(
+            tuple((
+                x_or_y,
+                number,
+                preceded(ws(tag("DO")), number),
+                preceded(ws(tag("STEP")), number),
+            )),
+            |n| GcellGrid {
+                axis: n.0,
+                start: n.1,
+                num: n.2,
+                step: n.3,
+            },
+        ),
         ws(tag(";")),
     ))(input)
 }
 
-fn tracks_rule_list(input: &str) -> IResult<&str, Vec<Track>> {
+pub(crate) fn tracks_rule_list(input: &str) -> IResult<&str, Vec<Track<'_>>> {
     many1(delimited(
         ws(tag("TRACKS")),
-        tuple((
-            x_or_y,
-            number,
-            preceded(ws(tag("DO")), number),
-            preceded(ws(tag("STEP")), number),
-            opt(preceded(ws(tag("LAYER")), many1(tstring))),
-        )),
+        map(
+            tuple((
+                x_or_y,
+                number,
+                preceded(ws(tag("DO")), number),
+                preceded(ws(tag("STEP")), number),
+                opt(preceded(ws(tag("LAYER")), many1(tstring))),
+            )),
+            |n| Track {
+                axis: n.0,
+                start: n.1,
+                num: n.2,
+                step: n.3,
+                layers: n.4.map(|n| n.into_iter().map(Cow::from).collect()),
+            },
+        ),
         ws(tag(";")),
     ))(input)
 }
@@ -146,37 +212,43 @@ fn row_type(input: &str) -> IResult<&str, &str> {
     alt((ws(tag("CORE")), ws(tag("ARRAYSITE"))))(input)
 }
 
-// return tuple includes information that
-//                      (data_type, has_range, has_number,has_name_map_string,
-//                       string_data,range_left,range_right,num_data)
+// {INTEGER | REAL} [RANGE min max] [value] or STRING [value]
+#[allow(clippy::type_complexity)]
 fn property_type_and_val(
     input: &str,
 ) -> IResult<
     &str,
     (
-        char, // data type of property
+        char, // data type of property, S, I or R
         Option<&str>,
-        Option<(i32, Option<(i32, i32)>)>,
-        Option<(f64, Option<(f64, f64)>)>,
+        Option<(Option<(i32, i32)>, Option<i32>)>,
+        Option<(Option<(f64, f64)>, Option<f64>)>,
     ),
 > {
     alt((
-        map(preceded(ws(tag("STRING")), tstring), |n| {
-            ('S', Some(n), None, None)
-        }),
-        map(preceded(ws(tag("INTEGER")), number), |n| {
-            ('I', None, Some((n, None)), None)
-        }),
-        map(preceded(ws(tag("REAL")), float), |n| {
-            ('R', None, None, Some((n, None)))
-        }),
         map(
-            preceded(ws(tag("INTEGER RANGE")), tuple((number, number, number))),
-            |n| ('R', None, Some((n.0, Some((n.1, n.2)))), None),
+            preceded(ws(tag("STRING")), opt(alt((qstring, tstring)))),
+            |n| ('S', n, None, None),
+        ),
+        map(
+            preceded(
+                ws(tag("INTEGER")),
+                tuple((
+                    opt(preceded(ws(tag("RANGE")), tuple((number, number)))),
+                    opt(number),
+                )),
+            ),
+            |n| ('I', None, Some(n), None),
         ),
         map(
-            preceded(ws(tag("REAL RANGE")), tuple((float, float, float))),
-            |n| ('R', None, None, Some((n.0, Some((n.1, n.2))))),
+            preceded(
+                ws(tag("REAL")),
+                tuple((
+                    opt(preceded(ws(tag("RANGE")), tuple((float, float)))),
+                    opt(float),
+                )),
+            ),
+            |n| ('R', None, None, Some(n)),
         ),
     ))(input)
 }
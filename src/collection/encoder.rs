@@ -1,149 +1,128 @@
 // common scope
-use nom::branch::alt;
-use nom::bytes::complete::tag;
-use nom::combinator::map;
+use nom::character::complete::alphanumeric1;
+use nom::combinator::{map_res, opt};
 use nom::sequence::preceded;
 use nom::IResult;
+use std::str::FromStr;
 
-use super::base::ws;
-
-pub fn orient_encode(input: &str) -> IResult<&str, i8> {
-    ws(alt((
-        map(tag("N"), |_| 0),
-        map(tag("W"), |_| 1),
-        map(tag("S"), |_| 2),
-        map(tag("E"), |_| 3),
-        map(tag("FN"), |_| 4),
-        map(tag("FW"), |_| 5),
-        map(tag("FS"), |_| 6),
-        map(tag("FE"), |_| 7),
-    )))(input)
+use super::base::{expect, tag, ws, Expected};
+use super::def_types::{
+    AntennaModel, Direction, NetUse, Orient, Pattern, PlacementStatus, RegionType, RoutingStatus,
+    ShapeType, Source,
+};
+
+// parse a DEF keyword into its enum through FromStr, `keywords` are reported when it fails
+fn keyword<'a, T: FromStr>(
+    keywords: &'static [&'static str],
+) -> impl FnMut(&'a str) -> IResult<&'a str, T> {
+    ws(move |input: &'a str| {
+        let res = map_res(alphanumeric1, |res: &str| res.parse::<T>())(input);
+        if res.is_err() {
+            for n in keywords {
+                expect(input, Expected::Token(n));
+            }
+        }
+        res
+    })
+}
+
+pub fn orient_encode(input: &str) -> IResult<&str, Orient> {
+    keyword(Orient::KEYWORDS)(input)
 }
 
 // source type must be preceded with "SOURCE" keyword
-pub fn source_type_encode(input: &str) -> IResult<&str, i8> {
-    preceded(
-        ws(tag("+ SOURCE")),
-        ws(alt((
-            map(tag("DIST"), |_| 0),
-            map(tag("NETLIST"), |_| 1),
-            map(tag("TIMING"), |_| 2),
-            map(tag("USER"), |_| 3),
-            map(tag("TEST"), |_| 4),
-        ))),
-    )(input)
+pub fn source_type_encode(input: &str) -> IResult<&str, Source> {
+    preceded(ws(tag("+ SOURCE")), keyword(Source::KEYWORDS))(input)
 }
 
 // shape type used in snet parser
-pub fn snet_shape_encode(input: &str) -> IResult<&str, i8> {
-    preceded(
-        ws(tag("+ SHAPE")),
-        alt((
-            map(ws(tag("RING")), |_| 0),
-            map(ws(tag("PADRING")), |_| 1),
-            map(ws(tag("BLOCKRING")), |_| 2),
-            map(ws(tag("STRIPE")), |_| 3),
-            map(ws(tag("FOLLOWPIN")), |_| 4),
-            map(ws(tag("IOWIRE")), |_| 5),
-            map(ws(tag("COREWIRE")), |_| 6),
-            map(ws(tag("BLOCKWIRE")), |_| 7),
-            map(ws(tag("BLOCKAGEWIRE")), |_| 8),
-            map(ws(tag("FILLWIRE")), |_| 9),
-            map(ws(tag("FILLWIREOPC")), |_| 10),
-            map(ws(tag("DRCFILL")), |_| 11),
-        )),
-    )(input)
+pub fn snet_shape_encode(input: &str) -> IResult<&str, ShapeType> {
+    preceded(ws(tag("+ SHAPE")), keyword(ShapeType::KEYWORDS))(input)
 }
 
 // compatible in net and snet
-pub fn use_mode_encode(input: &str) -> IResult<&str, i8> {
-    preceded(
-        ws(tag("+ USE")),
-        alt((
-            map(ws(tag("ANALOG")), |_| 0),
-            map(ws(tag("CLOCK")), |_| 1),
-            map(ws(tag("GROUND")), |_| 2),
-            map(ws(tag("POWER")), |_| 3),
-            map(ws(tag("RESET")), |_| 4),
-            map(ws(tag("SCAN")), |_| 5),
-            map(ws(tag("SIGNAL")), |_| 6),
-            map(ws(tag("TIEOFF")), |_| 7),
-        )),
-    )(input)
+pub fn use_mode_encode(input: &str) -> IResult<&str, NetUse> {
+    preceded(ws(tag("+ USE")), keyword(NetUse::KEYWORDS))(input)
 }
 
 // compatible in net and snet
-pub fn net_pattern_encode(input: &str) -> IResult<&str, i8> {
-    ws(preceded(
-        ws(tag("+ PATTERN")),
-        alt((
-            map(tag("BALANCED"), |_| 0),
-            map(tag("STEINER"), |_| 1),
-            map(tag("TRUNK"), |_| 2),
-            map(tag("WIREDLOGIC"), |_| 3),
-        )),
-    ))(input)
+pub fn net_pattern_encode(input: &str) -> IResult<&str, Pattern> {
+    preceded(ws(tag("+ PATTERN")), keyword(Pattern::KEYWORDS))(input)
+}
+
+pub fn snet_global_attribute_encode(input: &str) -> IResult<&str, RoutingStatus> {
+    preceded(ws(tag("+")), keyword(RoutingStatus::KEYWORDS))(input)
+}
+
+pub fn net_global_attribute_encode(input: &str) -> IResult<&str, RoutingStatus> {
+    keyword(RoutingStatus::KEYWORDS)(input)
+}
+
+pub fn pin_location_attribute_encode(input: &str) -> IResult<&str, PlacementStatus> {
+    preceded(ws(tag("+")), keyword(PlacementStatus::KEYWORDS))(input)
 }
 
-pub fn snet_global_attribute_encode(input: &str) -> IResult<&str, i8> {
-    ws(alt((
-        map(ws(tag("+ COVER")), |_| 0),
-        map(ws(tag("+ FIXED")), |_| 1),
-        map(ws(tag("+ ROUTED")), |_| 2),
-        map(ws(tag("+ SHIELD")), |_| 3),
-    )))(input)
+// VPIN location is written without "+"
+pub fn vpin_location_attribute_encode(input: &str) -> IResult<&str, PlacementStatus> {
+    preceded(opt(ws(tag("+"))), keyword(PlacementStatus::KEYWORDS))(input)
 }
 
-pub fn net_global_attribute_encode(input: &str) -> IResult<&str, i8> {
-    ws(alt((
-        map(tag("COVER"), |_| 0),
-        map(tag("FIXED"), |_| 1),
-        map(tag("ROUTED"), |_| 2),
-        map(tag("NOSHIELD"), |_| 3),
-    )))(input)
+pub fn pin_direction_encode(input: &str) -> IResult<&str, Direction> {
+    preceded(ws(tag("+ DIRECTION")), keyword(Direction::KEYWORDS))(input)
 }
 
-pub fn pin_location_attribute_encode(input: &str) -> IResult<&str, i8> {
-    ws(alt((
-        map(ws(tag("+ PLACED")), |_| 0),
-        map(ws(tag("+ FIXED")), |_| 1),
-        map(ws(tag("+ COVER")), |_| 2),
-    )))(input)
+pub fn pin_antenna_model_encode(input: &str) -> IResult<&str, AntennaModel> {
+    preceded(ws(tag("+ ANTENNAMODEL")), keyword(AntennaModel::KEYWORDS))(input)
 }
 
-pub fn pin_direction_encode(input: &str) -> IResult<&str, i8> {
-    preceded(
-        ws(tag("+ DIRECTION")),
-        alt((
-            map(ws(tag("INPUT")), |_| 0),
-            map(ws(tag("OUTPUT")), |_| 1),
-            map(ws(tag("INOUT")), |_| 2),
-            map(ws(tag("FEEDTHRU")), |_| 3),
-        )),
-    )(input)
+pub fn region_type_encode(input: &str) -> IResult<&str, RegionType> {
+    preceded(ws(tag("+ TYPE")), keyword(RegionType::KEYWORDS))(input)
 }
 
-// pub fn pin_antenna_model_encode(input: &str) -> IResult<&str, i32> {
-//     alt((
-//         map(tag("OXIDE1"), |_| 0),
-//         map(tag("OXIDE2"), |_| 1),
-//         map(tag("OXIDE3"), |_| 2),
-//         map(tag("OXIDE4"), |_| 3),
-//     ))(input)
-// }
-
-pub fn region_type_encode(input: &str) -> IResult<&str, i8> {
-    ws(preceded(
-        ws(tag("+ TYPE")),
-        alt((map(tag("FENCE"), |_| 0), map(tag("GUIDE"), |_| 1))),
-    ))(input)
+pub fn component_location_attribute_encode(input: &str) -> IResult<&str, PlacementStatus> {
+    preceded(ws(tag("+")), keyword(PlacementStatus::KEYWORDS))(input)
 }
 
-pub fn component_location_attribute_encode(input: &str) -> IResult<&str, i8> {
-    ws(alt((
-        map(ws(tag("+ FIXED")), |_| 0),
-        map(ws(tag("+ COVER")), |_| 1),
-        map(ws(tag("+ PLACED")), |_| 2),
-        map(ws(tag("+ UNPLACED")), |_| 3),
-    )))(input)
+#[cfg(test)]
+mod tests {
+    use crate::collection::def_types::*;
+    use crate::collection::encoder::*;
+
+    #[test]
+    fn test_keyword_encode() {
+        assert_eq!(orient_encode(" FN ").unwrap(), ("", Orient::FN));
+        assert_eq!(orient_encode("N;").unwrap(), (";", Orient::N));
+        assert!(orient_encode("NORTH").is_err());
+        assert_eq!(
+            snet_shape_encode("+ SHAPE FILLWIREOPC").unwrap(),
+            ("", ShapeType::FillWireOpc)
+        );
+        assert_eq!(
+            source_type_encode("+ SOURCE TEST").unwrap(),
+            ("", Source::Test)
+        );
+    }
+
+    #[test]
+    fn test_placement_status_encode() {
+        // components and pins share the same placement status
+        assert_eq!(
+            component_location_attribute_encode("+ FIXED").unwrap(),
+            pin_location_attribute_encode("+ FIXED").unwrap()
+        );
+        assert_eq!(
+            component_location_attribute_encode("+ PLACED").unwrap(),
+            ("", PlacementStatus::Placed)
+        );
+    }
+
+    #[test]
+    fn test_keyword_display() {
+        assert_eq!(NetUse::Tieoff.to_string(), "TIEOFF");
+        assert_eq!("FEEDTHRU".parse::<Direction>(), Ok(Direction::Feedthru));
+        assert_eq!(
+            "RING ".parse::<ShapeType>(),
+            Err(UnknownKeyword("RING ".to_string()))
+        );
+    }
 }
@@ -0,0 +1,62 @@
+// nom
+use nom::bytes::complete::take_till;
+use nom::combinator::map;
+use nom::error::{Error, ErrorKind};
+use nom::sequence::{delimited, pair, preceded, terminated};
+use nom::{Err, IResult};
+
+// def
+use super::base::{expect, tag, ws, Expected};
+use super::def_types::Extension;
+
+// BEGINEXT "tag" ... ENDEXT. The body is kept verbatim, comments included.
+pub fn extension_section(input: &str) -> IResult<&str, Extension<'_>> {
+    map(
+        pair(
+            preceded(
+                ws(tag("BEGINEXT")),
+                delimited(tag("\""), take_till(|c| c == '"'), tag("\"")),
+            ),
+            terminated(body, ws(tag("ENDEXT"))),
+        ),
+        |n| Extension {
+            tag: n.0.into(),
+            body: n.1.into(),
+        },
+    )(input)
+}
+
+// everything up to the ENDEXT token, which starts the input or follows whitespace and ends
+// the input or is followed by whitespace. It is expected at the end of the input when missing.
+fn body(input: &str) -> IResult<&str, &str> {
+    let mut from = 0;
+    while let Some(n) = input[from..].find("ENDEXT") {
+        let at = from + n;
+        let token_start = at == 0 || input[..at].ends_with(char::is_whitespace);
+        let after = &input[at + "ENDEXT".len()..];
+        if token_start && (after.is_empty() || after.starts_with(char::is_whitespace)) {
+            return Ok((&input[at..], &input[..at]));
+        }
+        from = at + 1;
+    }
+    expect(&input[input.len()..], Expected::Token("ENDEXT"));
+    Err(Err::Error(Error::new(input, ErrorKind::TakeUntil)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::collection::extension_parser::*;
+
+    #[test]
+    fn test_extension_section() {
+        let (rest, ext) =
+            extension_section("BEGINEXT \"tool\"\n  x ENDEXTRA y_ENDEXT\nENDEXT\nEND DESIGN")
+                .unwrap();
+        assert_eq!(rest, "END DESIGN");
+        assert_eq!(ext.tag, "tool");
+        assert_eq!(ext.body, "\n  x ENDEXTRA y_ENDEXT\n");
+        let (_, ext) = extension_section("BEGINEXT \"t\" a\tENDEXT").unwrap();
+        assert_eq!(ext.body, " a\t");
+        assert!(extension_section("BEGINEXT \"t\" xENDEXT").is_err());
+    }
+}
@@ -1,14 +1,16 @@
+use std::borrow::Cow;
+
 // nom
 
-use nom::bytes::complete::tag;
+use nom::combinator::map;
 
-use nom::multi::{many0, many1};
+use nom::multi::many1;
 use nom::sequence::{delimited, preceded, terminated, tuple};
 use nom::IResult;
 
 // def
-use super::base::{component_pattern, number, tstring, ws};
-use super::common::properties;
+use super::base::{component_pattern, number, tag, tstring, ws};
+use super::common::{members, properties};
 use super::def_types::Group;
 
 pub fn group_section(
@@ -17,7 +19,7 @@ pub fn group_section(
     &str,
     (
         i32, // numGroups
-        Vec<Group>,
+        Vec<Group<'_>>,
     ),
 > {
     delimited(
@@ -27,75 +29,29 @@ pub fn group_section(
                 number, // numGroups
                 ws(tag(";")),
             ),
-            many0(group_member),
+            members(group_member),
         )),
         ws(tag("END GROUPS")),
     )(input)
 }
 
-fn group_member(input: &str) -> IResult<&str, Group> {
-    delimited(
-        tag("-"),
-        tuple((
-            tstring,
-            many1(component_pattern),
-            preceded(tag("+ REGION"), tstring),
-            properties,
-        )),
-        ws(tag(";")),
+pub(crate) fn group_member(input: &str) -> IResult<&str, Group<'_>> {
+    map(
+        delimited(
+            tag("-"),
+            tuple((
+                tstring,
+                many1(component_pattern),
+                preceded(tag("+ REGION"), tstring),
+                properties,
+            )),
+            ws(tag(";")),
+        ),
+        |n| Group {
+            name: n.0.into(),
+            components: n.1.into_iter().map(Cow::from).collect(),
+            region: n.2.into(),
+            properties: n.3,
+        },
     )(input)
 }
-
-// #[cfg(test)]
-// mod tests {
-//     use super::def_types::*;
-//     use super::group_parser::*;
-//     use std::io::Read;
-
-//     #[test]
-//     fn test_group_section() {
-//         let mut input_def = std::fs::File::open("tests/group_test.def").unwrap();
-//         let mut data = String::new();
-//         input_def.read_to_string(&mut data).unwrap();
-//         let result = group_section(&data).unwrap();
-
-//         let group_section = result.1;
-
-//         let num = group_section.0;
-//         let groups = group_section.1;
-
-//         assert_eq!(num, 3);
-
-//         let group_1_feature = (
-//             Some(4000),
-//             Some(100000),
-//             Some(100000),
-//             GroupRegion::PreDefined("region1"),
-//             vec![
-//                 ("strprop", PropValue::SValue("\"aString\"")),
-//                 ("intprop", PropValue::IValue(1)),
-//                 ("realprop", PropValue::RValue(1.1)),
-//                 ("intrangeprop", PropValue::IValue(25)),
-//                 ("realrangeprop", PropValue::RValue(25.25)),
-//             ],
-//         );
-
-//         let group_2_feature = (
-//             Some(4000),
-//             None,
-//             None,
-//             GroupRegion::NewDefined(((0, 0), (100, 100))),
-//             vec![],
-//         );
-//         let group_3_feature = (None, None, None, GroupRegion::PreDefined("region2"), vec![]);
-
-//         assert_eq!(
-//             groups,
-//             vec![
-//                 (("group1", vec!["I3", "I2"],), group_1_feature),
-//                 (("group2", vec!["I4"],), group_2_feature),
-//                 (("region2", vec!["I7", "I8"],), group_3_feature),
-//             ]
-//         );
-//     }
-// }
@@ -1,33 +1,55 @@
 use nom::branch::alt;
-use nom::bytes::complete::tag;
-use nom::combinator::opt;
+use nom::character::complete::satisfy;
+use nom::combinator::{opt, recognize};
+use nom::multi::count;
 
 use nom::sequence::{delimited, tuple};
 use nom::IResult;
 
-use super::base::{float, ws};
+use super::base::{float, tag, ws};
 
+#[allow(clippy::type_complexity)]
 pub fn header_section(input: &str) -> IResult<&str, (Option<f64>, Option<&str>, Option<&str>)> {
     tuple((opt(version_num), opt(divider_char), opt(busbit_chars)))(input)
 }
 
-fn divider_char(input: &str) -> IResult<&str, &str> {
+pub(crate) fn divider_char(input: &str) -> IResult<&str, &str> {
     delimited(
         ws(tag("DIVIDERCHAR")),
-        alt((ws(tag("/")), ws(tag("\\")), ws(tag("%")), ws(tag("$")))),
+        quoted_or_bare(
+            |s| visible_chars(1)(s),
+            |s| alt((tag("/"), tag("\\"), tag("%"), tag("$"), tag("|")))(s),
+        ),
         ws(tag(";")),
     )(input)
 }
-fn busbit_chars(input: &str) -> IResult<&str, &str> {
+pub(crate) fn busbit_chars(input: &str) -> IResult<&str, &str> {
     delimited(
         ws(tag("BUSBITCHARS")),
-        alt((ws(tag("[]")), ws(tag("{}")), ws(tag("<>")))),
+        quoted_or_bare(
+            |s| visible_chars(2)(s),
+            |s| alt((tag("[]"), tag("{}"), tag("<>")))(s),
+        ),
         ws(tag(";")),
     )(input)
 }
 
+// DEF writes the characters quoted, ie. "/" and "[]", where any visible character is allowed.
+// The bare form is also accepted for the usual characters
+fn quoted_or_bare<'a, F, G>(quoted: F, bare: G) -> impl FnMut(&'a str) -> IResult<&'a str, &'a str>
+where
+    F: Fn(&'a str) -> IResult<&'a str, &'a str> + Copy + 'a,
+    G: Fn(&'a str) -> IResult<&'a str, &'a str> + Copy + 'a,
+{
+    ws(alt((delimited(tag("\""), quoted, tag("\"")), bare)))
+}
+
+fn visible_chars<'a>(n: usize) -> impl FnMut(&'a str) -> IResult<&'a str, &'a str> {
+    recognize(count(satisfy(|c| !c.is_whitespace()), n))
+}
+
 // parse version number
-fn version_num(
+pub(crate) fn version_num(
     input: &str,
 ) -> IResult<
     &str,
@@ -35,3 +57,25 @@ fn version_num(
 > {
     delimited(ws(tag("VERSION")), float, ws(tag(";")))(input)
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::collection::header_parser::*;
+
+    #[test]
+    fn test_header_section() {
+        let header = "VERSION 5.8 ;\nDIVIDERCHAR \"/\" ;\nBUSBITCHARS \"[]\" ;\n";
+        assert_eq!(
+            header_section(header).unwrap(),
+            ("", (Some(5.8), Some("/"), Some("[]")))
+        );
+        let header = "DIVIDERCHAR \":\" ;\nBUSBITCHARS \"()\" ;\n";
+        assert_eq!(
+            header_section(header).unwrap(),
+            ("", (None, Some(":"), Some("()")))
+        );
+        assert_eq!(divider_char("DIVIDERCHAR | ;").unwrap(), ("", "|"));
+        assert!(divider_char("DIVIDERCHAR \" \" ;").is_err());
+        assert!(busbit_chars("BUSBITCHARS \"(\" ;").is_err());
+    }
+}
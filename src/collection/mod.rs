@@ -2,9 +2,17 @@ pub mod base;
 pub mod blockage_parser;
 pub mod common;
 pub mod component_parser;
+pub mod def_error;
+pub mod def_owned;
+#[cfg(feature = "parallel")]
+pub mod def_parallel;
+pub mod def_parser;
+pub mod def_reader;
 pub mod def_types;
+pub mod def_writer;
 pub mod design_config_parser;
 pub mod encoder;
+pub mod extension_parser;
 pub mod fill_parser;
 pub mod group_parser;
 pub mod header_parser;
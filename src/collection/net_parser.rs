@@ -1,18 +1,22 @@
+use std::borrow::Cow;
+
 // nom
 use nom::branch::alt;
-use nom::bytes::complete::tag;
 use nom::combinator::{map, opt};
 use nom::multi::{many0, many1};
 use nom::sequence::{delimited, pair, preceded, terminated, tuple};
 use nom::IResult;
 
 // def
-use super::base::{float, itstring, number, tstring, ws};
-use super::common::{properties, pt_new, rect, route_body};
-use super::def_types::{Net, NetProperty, RegularWireBasic, RegularWireStmt, SubNet, Vpin};
+use super::base::{float, itstring, number, tag, tstring, ws};
+use super::common::{attributes, members, net_connection, property, pt_new, rect, route_elem};
+use super::def_types::{
+    Location, Net, NetUse, Pattern, PropValue, RegularWireBasic, RegularWireStmt, RouteBody,
+    RouteElem, Source, SubNet, SubNetPin, Vpin,
+};
 use super::encoder::{
-    net_global_attribute_encode, net_pattern_encode, orient_encode, pin_location_attribute_encode,
-    source_type_encode, use_mode_encode,
+    net_global_attribute_encode, net_pattern_encode, orient_encode, source_type_encode,
+    use_mode_encode, vpin_location_attribute_encode,
 };
 
 pub fn net_section(
@@ -21,286 +25,251 @@ pub fn net_section(
     &str,
     (
         i32, // numNet
-        Vec<Net>,
+        Vec<Net<'_>>,
     ),
 > {
     delimited(
         tag("NETS"),
         tuple((
             terminated(number, ws(tag(";"))), // numNet
-            many0(net_member),
+            members(net_member),
         )),
-        tag("END NETS"),
+        ws(tag("END NETS")),
     )(input)
 }
 
-fn net_member(input: &str) -> IResult<&str, Net> {
-    delimited(
-        tag("-"),
-        pair(
+// `+` attributes of a net, in any order
+enum NetAttribute<'a> {
+    ShieldNet(Cow<'a, str>),
+    Vpin(Vpin<'a>),
+    SubNet(SubNet<'a>),
+    Xtalk(i32),
+    Ndr(Cow<'a, str>),
+    Wiring(RegularWireStmt<'a>),
+    Source(Source),
+    FixedBump,
+    Frequency(f64),
+    Original(Cow<'a, str>),
+    Use(NetUse),
+    Pattern(Pattern),
+    EstCap(i32),
+    Weight(i32),
+    Property((Cow<'a, str>, PropValue<'a>)),
+}
+
+impl NetAttribute<'_> {
+    // keyword of an attribute given at most once
+    fn once(&self) -> Option<&'static str> {
+        match self {
+            NetAttribute::ShieldNet(_)
+            | NetAttribute::Vpin(_)
+            | NetAttribute::SubNet(_)
+            | NetAttribute::Wiring(_)
+            | NetAttribute::Property(_) => None,
+            NetAttribute::Xtalk(_) => Some("+ XTALK"),
+            NetAttribute::Ndr(_) => Some("+ NONDEFAULTRULE"),
+            NetAttribute::Source(_) => Some("+ SOURCE"),
+            NetAttribute::FixedBump => Some("+ FIXEDBUMP"),
+            NetAttribute::Frequency(_) => Some("+ FREQUENCY"),
+            NetAttribute::Original(_) => Some("+ ORIGINAL"),
+            NetAttribute::Use(_) => Some("+ USE"),
+            NetAttribute::Pattern(_) => Some("+ PATTERN"),
+            NetAttribute::EstCap(_) => Some("+ ESTCAP"),
+            NetAttribute::Weight(_) => Some("+ WEIGHT"),
+        }
+    }
+}
+
+fn net_attribute(input: &str) -> IResult<&str, NetAttribute<'_>> {
+    alt((
+        map(preceded(ws(tag("+ SHIELDNET")), tstring), |n| {
+            NetAttribute::ShieldNet(n.into())
+        }),
+        map(vpin, NetAttribute::Vpin),
+        map(subnet, NetAttribute::SubNet),
+        map(preceded(ws(tag("+ XTALK")), number), NetAttribute::Xtalk),
+        map(preceded(ws(tag("+ NONDEFAULTRULE")), itstring), |n| {
+            NetAttribute::Ndr(n.into())
+        }),
+        map(source_type_encode, NetAttribute::Source),
+        map(ws(tag("+ FIXEDBUMP")), |_| NetAttribute::FixedBump),
+        map(
+            preceded(ws(tag("+ FREQUENCY")), float),
+            NetAttribute::Frequency,
+        ),
+        map(preceded(ws(tag("+ ORIGINAL")), tstring), |n| {
+            NetAttribute::Original(n.into())
+        }),
+        map(use_mode_encode, NetAttribute::Use),
+        map(net_pattern_encode, NetAttribute::Pattern),
+        map(preceded(ws(tag("+ ESTCAP")), number), NetAttribute::EstCap),
+        map(preceded(ws(tag("+ WEIGHT")), number), NetAttribute::Weight),
+        map(property, NetAttribute::Property),
+        // "+" followed by a routing status, after the attributes starting with a keyword
+        map(regular_wiring, NetAttribute::Wiring),
+    ))(input)
+}
+
+pub(crate) fn net_member(input: &str) -> IResult<&str, Net<'_>> {
+    map(
+        delimited(
+            tag("-"),
             tuple((
                 tstring,
-                many0(delimited(
-                    ws(tag("(")),
-                    tuple((
-                        map(tstring, |n| match n {
-                            "PIN" => None,
-                            n => Some(n),
-                        }),
-                        tstring,
-                        map(
-                            opt(ws(tag("+ SYNTHESIZED"))),
-                            |res: Option<&str>| match res {
-                                Some(_) => true,
-                                None => false,
-                            },
-                        ),
-                    )),
-                    ws(tag(")")),
-                )),
-            )),
-            tuple((
-                many0(preceded(ws(tag("+ SHIELDNET")), tstring)),
-                many0(vpin),
-                many0(subnet),
-                opt(preceded(ws(tag("+ XTALK")), number)),
-                opt(preceded(ws(tag("+ NONDEFAULTRULE")), itstring)),
-                many0(regular_wiring),
-                net_property,
+                many0(net_connection),
+                attributes(net_attribute, NetAttribute::once),
             )),
+            ws(tag(";")),
         ),
-        ws(tag(";")),
+        |(name, connections, attrs)| {
+            let mut net = Net {
+                name: name.into(),
+                connections,
+                shield_nets: Vec::new(),
+                vpins: Vec::new(),
+                subnets: Vec::new(),
+                xtalk: None,
+                ndr: None,
+                wiring: Vec::new(),
+                source: None,
+                fixed_bump: false,
+                frequency: None,
+                original: None,
+                use_mode: None,
+                pattern: None,
+                est_cap: None,
+                weight: None,
+                properties: Vec::new(),
+            };
+            for attr in attrs {
+                match attr {
+                    NetAttribute::ShieldNet(n) => net.shield_nets.push(n),
+                    NetAttribute::Vpin(n) => net.vpins.push(n),
+                    NetAttribute::SubNet(n) => net.subnets.push(n),
+                    NetAttribute::Xtalk(n) => net.xtalk = Some(n),
+                    NetAttribute::Ndr(n) => net.ndr = Some(n),
+                    NetAttribute::Wiring(n) => net.wiring.push(n),
+                    NetAttribute::Source(n) => net.source = Some(n),
+                    NetAttribute::FixedBump => net.fixed_bump = true,
+                    NetAttribute::Frequency(n) => net.frequency = Some(n),
+                    NetAttribute::Original(n) => net.original = Some(n),
+                    NetAttribute::Use(n) => net.use_mode = Some(n),
+                    NetAttribute::Pattern(n) => net.pattern = Some(n),
+                    NetAttribute::EstCap(n) => net.est_cap = Some(n),
+                    NetAttribute::Weight(n) => net.weight = Some(n),
+                    NetAttribute::Property(n) => net.properties.push(n),
+                }
+            }
+            net
+        },
     )(input)
 }
 
-fn regular_wire_basic(input: &str) -> IResult<&str, RegularWireBasic> {
-    tuple((
-        tstring,
-        alt((
-            map(preceded(ws(tag("TAPERRULE")), tstring), |res: &str| {
-                Some(res)
-            }),
-            map(opt(ws(tag("TAPER"))), |_| None),
+fn regular_wire_basic(input: &str) -> IResult<&str, RegularWireBasic<'_>> {
+    map(
+        tuple((
+            tstring,
+            alt((
+                map(preceded(ws(tag("TAPERRULE")), tstring), |res: &str| {
+                    Some(res)
+                }),
+                map(opt(ws(tag("TAPER"))), |_| None),
+            )),
+            opt(preceded(ws(tag("STYLE")), number)),
+            regular_route_body,
         )),
-        opt(preceded(ws(tag("STYLE")), number)),
-        route_body,
-    ))(input)
+        |n| RegularWireBasic {
+            layer: n.0.into(),
+            taper_rule: n.1.map(Cow::from),
+            style: n.2,
+            route: n.3,
+        },
+    )(input)
 }
 
-fn regular_wiring(input: &str) -> IResult<&str, RegularWireStmt> {
-    tuple((
-        preceded(ws(tag("+")), net_global_attribute_encode),
-        many1(alt((
-            preceded(ws(tag("NEW")), regular_wire_basic),
-            regular_wire_basic,
-        ))),
-    ))(input)
+// route_body with the RECT patches and VIRTUAL points of regular wiring
+fn regular_route_body(input: &str) -> IResult<&str, RouteBody<'_>> {
+    many0(alt((
+        route_elem,
+        map(
+            preceded(
+                ws(tag("RECT")),
+                delimited(
+                    ws(tag("(")),
+                    tuple((number, number, number, number)),
+                    ws(tag(")")),
+                ),
+            ),
+            RouteElem::Rect,
+        ),
+        map(preceded(ws(tag("VIRTUAL")), pt_new), RouteElem::Virtual),
+    )))(input)
 }
 
-fn subnet_regular_wiring(input: &str) -> IResult<&str, RegularWireStmt> {
-    tuple((
-        net_global_attribute_encode,
-        many1(alt((
-            preceded(ws(tag("NEW")), regular_wire_basic),
-            regular_wire_basic,
-        ))),
-    ))(input)
+fn regular_wiring(input: &str) -> IResult<&str, RegularWireStmt<'_>> {
+    preceded(ws(tag("+")), subnet_regular_wiring)(input)
 }
 
-fn vpin(input: &str) -> IResult<&str, Vpin> {
-    tuple((
-        preceded(ws(tag("+ VPIN")), tstring),
-        preceded(ws(tag("LAYER")), tstring),
-        rect,
-        pin_location_attribute_encode,
-        pt_new,
-        orient_encode,
-    ))(input)
+fn subnet_regular_wiring(input: &str) -> IResult<&str, RegularWireStmt<'_>> {
+    map(
+        tuple((
+            net_global_attribute_encode,
+            many1(alt((
+                preceded(ws(tag("NEW")), regular_wire_basic),
+                regular_wire_basic,
+            ))),
+        )),
+        |n| RegularWireStmt {
+            status: n.0,
+            wires: n.1,
+        },
+    )(input)
+}
+
+fn vpin(input: &str) -> IResult<&str, Vpin<'_>> {
+    map(
+        tuple((
+            preceded(ws(tag("+ VPIN")), tstring),
+            preceded(ws(tag("LAYER")), tstring),
+            rect,
+            vpin_location_attribute_encode,
+            pt_new,
+            orient_encode,
+        )),
+        |n| Vpin {
+            name: n.0.into(),
+            layer: n.1.into(),
+            rect: n.2,
+            location: Location {
+                status: n.3,
+                point: n.4,
+                orient: n.5,
+            },
+        },
+    )(input)
 }
 
-fn subnet(input: &str) -> IResult<&str, SubNet> {
-    tuple((
+fn subnet(input: &str) -> IResult<&str, SubNet<'_>> {
+    map(
         tuple((
             preceded(ws(tag("+ SUBNET")), tstring),
             many1(delimited(
                 ws(tag("(")),
                 map(pair(tstring, tstring), |n| match n.0 {
-                    "VPIN" => (None, Some(n.1), None),
-                    "PIN" => (None, None, Some(n.1)),
-                    _ => (Some(n.0), None, Some(n.1)),
+                    "VPIN" => SubNetPin::Vpin(n.1.into()),
+                    "PIN" => SubNetPin::Pin(n.1.into()),
+                    _ => SubNetPin::Component(n.0.into(), n.1.into()),
                 }),
                 ws(tag(")")),
             )),
-        )),
-        tuple((
             opt(preceded(ws(tag("NONDEFAULTRULE")), itstring)),
             many0(subnet_regular_wiring),
         )),
-    ))(input)
-}
-
-fn net_property(input: &str) -> IResult<&str, NetProperty> {
-    tuple((
-        opt(source_type_encode),
-        map(opt(ws(tag("+ FIXEDBUMP"))), |res: Option<&str>| match res {
-            Some(_) => true,
-            None => false,
-        }),
-        opt(preceded(ws(tag("+ FREQUENCY")), float)),
-        opt(preceded(ws(tag("+ ORIGINAL")), tstring)),
-        opt(use_mode_encode),
-        opt(net_pattern_encode),
-        opt(preceded(ws(tag("+ ESTCAP")), number)),
-        opt(preceded(ws(tag("+ WEIGHT")), number)),
-        properties,
-    ))(input)
+        |n| SubNet {
+            name: n.0.into(),
+            pins: n.1,
+            ndr: n.2.map(Cow::from),
+            wiring: n.3,
+        },
+    )(input)
 }
-
-// #[cfg(test)]
-// mod tests {
-
-//     use super::def_types::PropValue;
-//     use super::def_types::RouteElem;
-//     use super::net_parser::*;
-//     use std::io::Read;
-
-//     #[test]
-//     fn test_net_section() {
-//         let mut input_def = std::fs::File::open("tests/net_test.def").unwrap();
-//         let mut data = String::new();
-//         input_def.read_to_string(&mut data).unwrap();
-//         let result = net_section(&data).unwrap();
-
-//         let net_section = result.1;
-
-//         let num = net_section.0;
-//         let nets = net_section.1;
-
-//         assert_eq!(num, 6);
-
-//         let net_1_feature = (
-//             vec![], // sheildnet
-//             vec![], // vpin
-//             vec![], // subnet
-//             None,   // xtalk
-//             None,   // ndr
-//             vec![],
-//             (
-//                 Some(4),
-//                 false, // fixedbump
-//                 None,  // frequency
-//                 None,  // original
-//                 None,  // use
-//                 None,  // pattern
-//                 None,  // EXTCAP
-//                 None,  // WEIGHT
-//                 vec![],
-//             ), // netproperty
-//         );
-
-//         let net_2_feature = (
-//             vec!["SN1", "VDD"], // shieldnet
-//             vec![
-//                 (
-//                     "N1_VP0",
-//                     Some("M3"),
-//                     ((-333, -333), (333, 333)),
-//                     Some(0),
-//                     Some((189560, 27300)),
-//                     Some(0),
-//                 ),
-//                 ("N1_VP8", None, ((-333, -333), (333, 333)), None, None, None),
-//             ], // vpin
-//             vec![(
-//                 (
-//                     "N1_SUB0",
-//                     vec![
-//                         (Some("I2"), None, Some("A")),
-//                         (None, None, Some("P1")),
-//                         (None, Some("N1_VP9"), None),
-//                     ],
-//                 ),
-//                 (
-//                     Some("RULE1"),
-//                     vec![(
-//                         2,
-//                         vec![(
-//                             "M1",
-//                             None,
-//                             None,
-//                             vec![
-//                                 RouteElem::Pt((Some(168280), Some(63300), Some(700))),
-//                                 RouteElem::Via(((None, Some(64500), None), "M1_M2")),
-//                                 RouteElem::Via(((Some(169400), None, Some(800)), "M2_M3")),
-//                             ],
-//                         )],
-//                     )],
-//                 ),
-//             )], // subnet
-//             Some(2),
-//             Some("RULE1"),
-//             vec![(
-//                 2,
-//                 vec![
-//                     (
-//                         "M2",
-//                         None,
-//                         None,
-//                         vec![
-//                             RouteElem::Pt((Some(14000), Some(341440), None)),
-//                             RouteElem::Pt((Some(9600), None, None)),
-//                             RouteElem::Via(((None, Some(282400), None), "nd1VIA12")),
-//                             RouteElem::Via(((Some(2400), None, None), "TURNM1_1")),
-//                         ],
-//                     ),
-//                     (
-//                         "M1",
-//                         None,
-//                         None,
-//                         vec![
-//                             RouteElem::Pt((Some(2400), Some(282400), None)),
-//                             RouteElem::Pt((Some(240), None, None)),
-//                         ],
-//                     ),
-//                 ],
-//             )],
-//             (
-//                 Some(1),
-//                 true,
-//                 Some(100),
-//                 Some("N2"),
-//                 Some(6),
-//                 Some(1),
-//                 Some(1500000),
-//                 Some(100),
-//                 vec![
-//                     ("strprop", PropValue::SValue("\"aString\"")),
-//                     ("intprop", PropValue::IValue(1)),
-//                     ("realprop", PropValue::RValue(1.1)),
-//                     ("intrangeprop", PropValue::IValue(25)),
-//                     ("realrangeprop", PropValue::RValue(25.25)),
-//                 ],
-//             ),
-//         );
-
-//         assert_eq!(
-//             nets,
-//             vec![
-//                 (
-//                     (
-//                         "SCAN",
-//                         vec![
-//                             (Some("scancell1"), "PA10", true),
-//                             (Some("scancell2"), "PA2", true)
-//                         ]
-//                     ),
-//                     net_1_feature,
-//                 ),
-//                 (
-//                     ("N1", vec![(Some("I1"), "A", false), (None, "P0", false)],),
-//                     net_2_feature,
-//                 )
-//             ]
-//         );
-//     }
-// }
@@ -1,15 +1,16 @@
+use std::borrow::Cow;
+
 // nom
 
-use nom::bytes::complete::tag;
 use nom::combinator::{map, opt, recognize};
 use nom::multi::many0;
 use nom::sequence::{delimited, pair, preceded, terminated, tuple};
 use nom::IResult;
 
 // def
-use super::base::{number, tstring, ws};
-use super::common::properties;
-use super::def_types::Ndr;
+use super::base::{number, tag, tstring, ws};
+use super::common::{members, properties};
+use super::def_types::{Ndr, NdrLayer};
 
 pub fn ndr_section(
     input: &str,
@@ -17,126 +18,66 @@ pub fn ndr_section(
     &str,
     (
         i32, // number of Ndr
-        Vec<Ndr>,
+        Vec<Ndr<'_>>,
     ),
 > {
     delimited(
         tag("NONDEFAULTRULES"),
-        tuple((terminated(number, ws(tag(";"))), many0(ndr_member))),
+        tuple((terminated(number, ws(tag(";"))), members(ndr_member))),
         tag("END NONDEFAULTRULES"),
     )(input)
 }
 
-fn ndr_member(input: &str) -> IResult<&str, Ndr> {
-    delimited(
-        tag("-"),
-        pair(
-            tstring,
-            tuple((
-                map(
-                    opt(ws(tag("+ HARDSPACING"))),
-                    |res: Option<&str>| match res {
-                        Some(_) => true,
-                        None => false,
-                    },
-                ),
-                many0(ndr_layer),
-                // be cautious with space ending in the following two tag parser
-                many0(preceded(recognize(tag("+ VIA ")), tstring)),
-                many0(preceded(recognize(tag("+ VIARULE ")), tstring)),
-                many0(preceded(
-                    recognize(tag("+ MINCUTS")),
-                    tuple((tstring, number)),
+pub(crate) fn ndr_member(input: &str) -> IResult<&str, Ndr<'_>> {
+    map(
+        delimited(
+            tag("-"),
+            pair(
+                tstring,
+                tuple((
+                    map(opt(ws(tag("+ HARDSPACING"))), |res: Option<&str>| {
+                        res.is_some()
+                    }),
+                    many0(ndr_layer),
+                    // be cautious with space ending in the following two tag parser
+                    many0(preceded(recognize(tag("+ VIA ")), tstring)),
+                    many0(preceded(recognize(tag("+ VIARULE ")), tstring)),
+                    many0(preceded(
+                        recognize(tag("+ MINCUTS")),
+                        tuple((tstring, number)),
+                    )),
+                    properties,
                 )),
-                properties,
-            )),
+            ),
+            ws(tag(";")),
         ),
-        ws(tag(";")),
+        |(name, feature)| Ndr {
+            name: name.into(),
+            hard_spacing: feature.0,
+            layers: feature.1,
+            vias: feature.2.into_iter().map(Cow::from).collect(),
+            via_rules: feature.3.into_iter().map(Cow::from).collect(),
+            min_cuts: feature.4.into_iter().map(|n| (n.0.into(), n.1)).collect(),
+            properties: feature.5,
+        },
     )(input)
 }
 
-fn ndr_layer(
-    input: &str,
-) -> IResult<
-    &str,
-    (
-        &str,        // name
-        i32,         // width
-        Option<i32>, // diagwidth
-        Option<i32>, // spacing
-        Option<i32>, // wireext
-    ),
-> {
-    tuple((
-        preceded(tag("+ LAYER"), tstring),
-        preceded(tag("WIDTH"), number),
-        opt(preceded(ws(tag("DIAGWIDTH")), number)),
-        opt(preceded(ws(tag("SPACING")), number)),
-        opt(preceded(ws(tag("WIREEXT")), number)),
-    ))(input)
+fn ndr_layer(input: &str) -> IResult<&str, NdrLayer<'_>> {
+    map(
+        tuple((
+            preceded(tag("+ LAYER"), tstring),
+            preceded(tag("WIDTH"), number),
+            opt(preceded(ws(tag("DIAGWIDTH")), number)),
+            opt(preceded(ws(tag("SPACING")), number)),
+            opt(preceded(ws(tag("WIREEXT")), number)),
+        )),
+        |n| NdrLayer {
+            name: n.0.into(),
+            width: n.1,
+            diag_width: n.2,
+            spacing: n.3,
+            wire_ext: n.4,
+        },
+    )(input)
 }
-
-// #[cfg(test)]
-// mod tests {
-//     use super::def_types::*;
-//     use super::nondefaultrule_parser::*;
-//     use std::io::Read;
-
-//     #[test]
-//     fn test_ndr_section() {
-//         let mut input_def = std::fs::File::open("tests/ndr_test.def").unwrap();
-//         let mut data = String::new();
-//         input_def.read_to_string(&mut data).unwrap();
-//         let result = ndr_section(&data).unwrap();
-
-//         let ndr_section = result.1;
-
-//         let num = ndr_section.0;
-//         let ndrs = ndr_section.1;
-
-//         assert_eq!(num, 1);
-
-//         let net_1_feature = (
-//             false,
-//             vec![
-//                 ("METAL1", 10.1, 8.01, 2.2, 1.1),
-//                 ("M2", 10.1, 0.0, 2.2, 0.0),
-//                 ("M3", 11.1, 0.0, 3.2, 0.0),
-//             ],
-//             vec!["M1_M2", "M2_M3"],
-//             vec!["VIAGEN12"],
-//             vec![("V1", 2)],
-//             vec![
-//                 ("strprop", PropValue::SValue("\"aString\"")),
-//                 ("intprop", PropValue::IValue(1)),
-//                 ("realprop", PropValue::RValue(1.1)),
-//                 ("intrangeprop", PropValue::IValue(25)),
-//                 ("realrangeprop", PropValue::RValue(25.25)),
-//             ],
-//         );
-
-//         let net_2_feature = (
-//             true,
-//             vec![
-//                 ("METAL1", 10.1, 8.01, 2.2, 1.1),
-//                 ("M2", 10.1, 0.0, 2.2, 0.0),
-//                 ("M3", 11.1, 0.0, 3.2, 0.0),
-//             ],
-//             vec!["M1_M2", "M2_M3"],
-//             vec!["VIAGEN12"],
-//             vec![("V1", 2)],
-//             vec![
-//                 ("strprop", PropValue::SValue("\"aString\"")),
-//                 ("intprop", PropValue::IValue(1)),
-//                 ("realprop", PropValue::RValue(1.1)),
-//                 ("intrangeprop", PropValue::IValue(25)),
-//                 ("realrangeprop", PropValue::RValue(25.25)),
-//             ],
-//         );
-
-//         assert_eq!(
-//             ndrs,
-//             vec![("DEFAULT", net_1_feature,), ("RULE2", net_2_feature,),]
-//         );
-//     }
-// }
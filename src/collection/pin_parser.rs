@@ -1,17 +1,20 @@
+use std::borrow::Cow;
+
 // nom
 use nom::branch::alt;
-use nom::bytes::complete::tag;
-use nom::combinator::{map, opt};
-use nom::multi::{many0, many1};
+use nom::combinator::{map, map_opt, opt};
 use nom::sequence::{delimited, preceded, terminated, tuple};
 use nom::IResult;
 
 // def
-use super::base::{number, qstring, tstring, ws};
-use super::common::{pt_list, pt_new, rect};
-use super::def_types::{Location, Pin, Port, PortElem};
+use super::base::{number, qstring, tag, tstring, ws};
+use super::common::{attributes, members, pt_list, pt_new, rect};
+use super::def_types::{
+    Direction, LayerRule, Location, NetUse, Pin, PinAntenna, PinPort, Port, PortElem,
+};
 use super::encoder::{
-    orient_encode, pin_direction_encode, pin_location_attribute_encode, use_mode_encode,
+    orient_encode, pin_antenna_model_encode, pin_direction_encode, pin_location_attribute_encode,
+    use_mode_encode,
 };
 
 pub fn pin_section(
@@ -20,157 +23,321 @@ pub fn pin_section(
     &str,
     (
         i32, // number of PINS
-        Vec<Pin>,
+        Vec<Pin<'_>>,
     ),
 > {
     delimited(
         tag("PINS"),
-        tuple((terminated(number, ws(tag(";"))), many0(pin_member))),
+        tuple((terminated(number, ws(tag(";"))), members(pin_member))),
         ws(tag("END PINS")),
     )(input)
 }
 
-fn pin_member(input: &str) -> IResult<&str, Pin> {
-    delimited(
-        tag("-"),
-        tuple((
-            tuple((tstring, preceded(ws(tag("+ NET")), tstring))),
+// `+` attributes of a pin, its ports included, in any order
+enum PinAttribute<'a> {
+    Special,
+    Direction(Direction),
+    NetExpr(Cow<'a, str>),
+    SupplySensitivity(Cow<'a, str>),
+    GroundSensitivity(Cow<'a, str>),
+    Use(NetUse),
+    Antenna(PinAntenna<'a>),
+    Port, // + PORT, starting the next of many ports
+    PortElem(PortElem<'a>),
+    Location(Location),
+}
+
+impl PinAttribute<'_> {
+    // keyword of an attribute given at most once
+    fn once(&self) -> Option<&'static str> {
+        match self {
+            PinAttribute::Special => Some("+ SPECIAL"),
+            PinAttribute::Direction(_) => Some("+ DIRECTION"),
+            PinAttribute::NetExpr(_) => Some("+ NETEXPR"),
+            PinAttribute::SupplySensitivity(_) => Some("+ SUPPLYSENSITIVITY"),
+            PinAttribute::GroundSensitivity(_) => Some("+ GROUNDSENSITIVITY"),
+            PinAttribute::Use(_) => Some("+ USE"),
+            PinAttribute::Antenna(_)
+            | PinAttribute::Port
+            | PinAttribute::PortElem(_)
+            | PinAttribute::Location(_) => None,
+        }
+    }
+}
+
+fn pin_attribute(input: &str) -> IResult<&str, PinAttribute<'_>> {
+    alt((
+        map(ws(tag("+ SPECIAL")), |_| PinAttribute::Special),
+        map(pin_direction_encode, PinAttribute::Direction),
+        map(preceded(ws(tag("+ NETEXPR")), qstring), |n| {
+            PinAttribute::NetExpr(n.into())
+        }),
+        map(preceded(ws(tag("+ SUPPLYSENSITIVITY")), tstring), |n| {
+            PinAttribute::SupplySensitivity(n.into())
+        }),
+        map(preceded(ws(tag("+ GROUNDSENSITIVITY")), tstring), |n| {
+            PinAttribute::GroundSensitivity(n.into())
+        }),
+        map(use_mode_encode, PinAttribute::Use),
+        map(pin_antenna, PinAttribute::Antenna),
+        map(ws(tag("+ PORT")), |_| PinAttribute::Port),
+        map(pin_port_element, PinAttribute::PortElem),
+        map(port_location, PinAttribute::Location),
+    ))(input)
+}
+
+pub(crate) fn pin_member(input: &str) -> IResult<&str, Pin<'_>> {
+    map_opt(
+        delimited(
+            tag("-"),
             tuple((
-                map(opt(ws(tag("+ SPECIAL"))), |n| match n {
-                    Some(_) => true,
-                    None => false,
-                }),
-                opt(pin_direction_encode),
-                opt(preceded(ws(tag("+ NETEXPR")), qstring)),
-                opt(preceded(ws(tag("+ SUPPLYSENSITIVITY")), tstring)),
-                opt(preceded(ws(tag("+ GROUNDSENSITIVITY")), tstring)),
-                opt(use_mode_encode),
-                pin_port,
+                tstring,
+                preceded(ws(tag("+ NET")), tstring),
+                attributes(pin_attribute, PinAttribute::once),
             )),
-        )),
-        ws(tag(";")),
+            ws(tag(";")),
+        ),
+        |(name, net, attrs)| {
+            // elements and location of each port, and whether they follow + PORT
+            let mut ports: Vec<(Vec<PortElem>, Option<Location>)> = Vec::new();
+            let mut many = false;
+            let mut pin = Pin {
+                name: name.into(),
+                net: net.into(),
+                special: false,
+                direction: None,
+                net_expr: None,
+                supply_sensitivity: None,
+                ground_sensitivity: None,
+                use_mode: None,
+                antenna: Vec::new(),
+                port: Port::ManyPorts(Vec::new()),
+            };
+            for attr in attrs {
+                match attr {
+                    PinAttribute::Special => pin.special = true,
+                    PinAttribute::Direction(n) => pin.direction = Some(n),
+                    PinAttribute::NetExpr(n) => pin.net_expr = Some(n),
+                    PinAttribute::SupplySensitivity(n) => pin.supply_sensitivity = Some(n),
+                    PinAttribute::GroundSensitivity(n) => pin.ground_sensitivity = Some(n),
+                    PinAttribute::Use(n) => pin.use_mode = Some(n),
+                    PinAttribute::Antenna(n) => pin.antenna.push(n),
+                    PinAttribute::Port => {
+                        if !many && !ports.is_empty() {
+                            return None;
+                        }
+                        many = true;
+                        ports.push((Vec::new(), None));
+                    }
+                    PinAttribute::PortElem(n) => {
+                        if ports.is_empty() {
+                            ports.push((Vec::new(), None));
+                        }
+                        ports.last_mut()?.0.push(n);
+                    }
+                    PinAttribute::Location(n) => {
+                        if ports.is_empty() {
+                            ports.push((Vec::new(), None));
+                        }
+                        let location = &mut ports.last_mut()?.1;
+                        if location.is_some() {
+                            return None;
+                        }
+                        *location = Some(n);
+                    }
+                }
+            }
+            // every port has elements and a location
+            let mut ports = ports
+                .into_iter()
+                .map(|(elems, location)| match (elems.is_empty(), location) {
+                    (false, Some(location)) => Some(PinPort { elems, location }),
+                    _ => None,
+                })
+                .collect::<Option<Vec<PinPort>>>()?;
+            pin.port = match (many, ports.len()) {
+                (true, _) => Port::ManyPorts(ports),
+                (false, 1) => Port::SinglePort(ports.pop()?),
+                _ => return None,
+            };
+            Some(pin)
+        },
     )(input)
 }
 
-fn port_location(input: &str) -> IResult<&str, Location> {
-    tuple((pin_location_attribute_encode, pt_new, orient_encode))(input)
-}
-
-fn pin_port(input: &str) -> IResult<&str, Port> {
+fn pin_antenna(input: &str) -> IResult<&str, PinAntenna<'_>> {
+    let layer = |input| preceded(ws(tag("LAYER")), tstring)(input);
     alt((
         map(
-            many1(preceded(
-                ws(tag("+ PORT")),
-                tuple((many1(pin_port_element), port_location)),
+            tuple((
+                preceded(ws(tag("+ ANTENNAPINPARTIALMETALAREA")), number),
+                opt(layer),
             )),
-            |n| Port::ManyPorts(n),
+            |n| PinAntenna::PartialMetalArea(n.0, n.1.map(Cow::from)),
+        ),
+        map(
+            tuple((
+                preceded(ws(tag("+ ANTENNAPINPARTIALMETALSIDEAREA")), number),
+                opt(layer),
+            )),
+            |n| PinAntenna::PartialMetalSideArea(n.0, n.1.map(Cow::from)),
+        ),
+        map(
+            tuple((
+                preceded(ws(tag("+ ANTENNAPINPARTIALCUTAREA")), number),
+                opt(layer),
+            )),
+            |n| PinAntenna::PartialCutArea(n.0, n.1.map(Cow::from)),
+        ),
+        map(
+            tuple((
+                preceded(ws(tag("+ ANTENNAPINDIFFAREA")), number),
+                opt(layer),
+            )),
+            |n| PinAntenna::DiffArea(n.0, n.1.map(Cow::from)),
+        ),
+        map(pin_antenna_model_encode, PinAntenna::Model),
+        map(
+            tuple((
+                preceded(ws(tag("+ ANTENNAPINGATEAREA")), number),
+                opt(layer),
+            )),
+            |n| PinAntenna::GateArea(n.0, n.1.map(Cow::from)),
+        ),
+        map(
+            tuple((preceded(ws(tag("+ ANTENNAPINMAXAREACAR")), number), layer)),
+            |n| PinAntenna::MaxAreaCar(n.0, n.1.into()),
+        ),
+        map(
+            tuple((
+                preceded(ws(tag("+ ANTENNAPINMAXSIDEAREACAR")), number),
+                layer,
+            )),
+            |n| PinAntenna::MaxSideAreaCar(n.0, n.1.into()),
+        ),
+        map(
+            tuple((preceded(ws(tag("+ ANTENNAPINMAXCUTCAR")), number), layer)),
+            |n| PinAntenna::MaxCutCar(n.0, n.1.into()),
         ),
-        map(tuple((many1(pin_port_element), port_location)), |n| {
-            Port::SinglePort(n)
-        }),
     ))(input)
 }
 
-fn pin_port_element(input: &str) -> IResult<&str, PortElem> {
+fn port_location(input: &str) -> IResult<&str, Location> {
+    map(
+        tuple((pin_location_attribute_encode, pt_new, orient_encode)),
+        |n| Location {
+            status: n.0,
+            point: n.1,
+            orient: n.2,
+        },
+    )(input)
+}
+
+fn pin_port_element(input: &str) -> IResult<&str, PortElem<'_>> {
     alt((
         map(
             tuple((
                 preceded(ws(tag("+ LAYER")), tstring),
+                opt(preceded(ws(tag("MASK")), number)),
                 opt(alt((
-                    preceded(tag("SPACING"), number),
-                    preceded(tag("DESIGNRULEWIDTH"), number),
+                    map(preceded(tag("SPACING"), number), LayerRule::Spacing),
+                    map(
+                        preceded(tag("DESIGNRULEWIDTH"), number),
+                        LayerRule::DesignRuleWidth,
+                    ),
                 ))),
                 rect,
             )),
-            |n| PortElem::Layer(n),
+            |n| PortElem::Layer {
+                layer: n.0.into(),
+                mask: n.1,
+                rule: n.2,
+                rect: n.3,
+            },
         ),
         map(
             tuple((
                 preceded(ws(tag("+ POLYGON")), tstring),
+                opt(preceded(ws(tag("MASK")), number)),
                 opt(alt((
-                    preceded(tag("SPACING"), number),
-                    preceded(tag("DESIGNRULEWIDTH"), number),
+                    map(preceded(tag("SPACING"), number), LayerRule::Spacing),
+                    map(
+                        preceded(tag("DESIGNRULEWIDTH"), number),
+                        LayerRule::DesignRuleWidth,
+                    ),
                 ))),
                 pt_list,
             )),
-            |n| PortElem::Polygon(n),
+            |n| PortElem::Polygon {
+                layer: n.0.into(),
+                mask: n.1,
+                rule: n.2,
+                points: n.3,
+            },
+        ),
+        map(
+            tuple((
+                preceded(ws(tag("+ VIA")), tstring),
+                opt(preceded(ws(tag("MASK")), number)),
+                pt_new,
+            )),
+            |n| PortElem::Via {
+                via: n.0.into(),
+                mask: n.1,
+                point: n.2,
+            },
         ),
-        map(tuple((preceded(ws(tag("+ VIA")), tstring), pt_new)), |n| {
-            PortElem::Via(n)
-        }),
     ))(input)
 }
 
-// #[cfg(test)]
-// mod tests {
-
-//     use super::pin_parser::*;
-//     use std::io::Read;
-
-//     #[test]
-//     fn test_pin_section() {
-//         let mut input_def = std::fs::File::open("tests/pin_test.def").unwrap();
-//         let mut data = String::new();
-//         input_def.read_to_string(&mut data).unwrap();
-//         let result = pin_section(&data).unwrap();
+#[cfg(test)]
+mod tests {
+    use crate::collection::def_types::*;
+    use crate::collection::pin_parser::*;
 
-//         let pin_section = result.1;
+    #[test]
+    fn test_pin_attributes_after_port() {
+        let (_, pin) = pin_member(
+            "- p + NET n + LAYER M1 ( 0 0 ) ( 1 1 ) + PLACED ( 0 0 ) N \
+             + DIRECTION INPUT + USE SIGNAL ;",
+        )
+        .unwrap();
+        assert_eq!(pin.direction, Some(Direction::Input));
+        assert_eq!(pin.use_mode, Some(NetUse::Signal));
+        match pin.port {
+            Port::SinglePort(port) => {
+                assert_eq!(port.elems.len(), 1);
+                assert_eq!(port.location.point, (0, 0));
+            }
+            Port::ManyPorts(_) => panic!("expected a single port"),
+        }
+    }
 
-//         let num = pin_section.0;
-//         let pins = pin_section.1;
-
-//         let pin_1_feature = (
-//             // vec![
-//             //     PinAntenna::PartialMetalArea((5, Some("METAL1"))),
-//             //     PinAntenna::PartialMetalArea((5, Some("M2"))),
-//             //     PinAntenna::PartialMetalSideArea((10, Some("METAL1"))),
-//             //     PinAntenna::PartialMetalSideArea((10, Some("M2"))),
-//             //     PinAntenna::PartialCutArea((35, Some("V1"))),
-//             //     PinAntenna::PartialCutArea((35, Some("V2"))),
-//             //     PinAntenna::DiffArea((20, Some("M1"))),
-//             //     PinAntenna::DiffArea((20, Some("M2"))),
-//             //     PinAntenna::Model(Some(0)),
-//             //     PinAntenna::GateArea((15, Some("M1"))),
-//             //     PinAntenna::GateArea((15, Some("M2"))),
-//             //     PinAntenna::MaxAreaCar((25, "M2")),
-//             //     PinAntenna::MaxSideAreaCar((30, "M1")),
-//             //     PinAntenna::MaxCutCar((40, "M1")),
-//             // ],
-//             true,       // Whether special
-//             Some(0),    // direction
-//             None,       // NetExpre
-//             Some("P1"), // PowerPin name
-//             Some("P2"), // GroundPin name
-//             Some(6),    // pin mode
-//             vec![
-//                 (
-//                     vec![
-//                         PortElem::Layer(("M2", None, ((0, 0), (30, 135)))),
-//                         PortElem::Via(("VIAGEN12_0", (0, 100))),
-//                     ],
-//                     1,
-//                     (45, -2160),
-//                     0,
-//                 ),
-//                 (
-//                     vec![
-//                         PortElem::Layer(("M1", None, ((0, 0), (30, 135)))),
-//                         PortElem::Via(("M1_M2", (100, 0))),
-//                     ],
-//                     2,
-//                     (0, -1000),
-//                     0,
-//                 ),
-//                 (
-//                     vec![PortElem::Layer(("M3", None, ((0, 0), (30, 135))))],
-//                     0,
-//                     (1000, -1000),
-//                     0,
-//                 ),
-//             ],
-//         );
-
-//         assert_eq!(num, 11);
-//         assert_eq!(pins, vec![(("P0", "N0"), pin_1_feature)]);
-//     }
-// }
+    #[test]
+    fn test_pin_ports() {
+        let (_, pin) = pin_member(
+            "- p + NET n + PORT + LAYER M1 ( 0 0 ) ( 1 1 ) + FIXED ( 0 0 ) N \
+             + SPECIAL + PORT + PLACED ( 5 5 ) S + VIA V1 ( 1 1 ) ;",
+        )
+        .unwrap();
+        assert!(pin.special);
+        match pin.port {
+            Port::ManyPorts(ports) => {
+                assert_eq!(ports.len(), 2);
+                assert_eq!(ports[1].location.point, (5, 5));
+            }
+            Port::SinglePort(_) => panic!("expected many ports"),
+        }
+        // no location, two locations in one port, shapes before the first + PORT
+        assert!(pin_member("- p + NET n + LAYER M1 ( 0 0 ) ( 1 1 ) ;").is_err());
+        assert!(pin_member(
+            "- p + NET n + LAYER M1 ( 0 0 ) ( 1 1 ) + PLACED ( 0 0 ) N + PLACED ( 1 1 ) N ;"
+        )
+        .is_err());
+        assert!(pin_member(
+            "- p + NET n + LAYER M1 ( 0 0 ) ( 1 1 ) + PLACED ( 0 0 ) N \
+             + PORT + LAYER M1 ( 0 0 ) ( 1 1 ) + PLACED ( 0 0 ) N ;"
+        )
+        .is_err());
+    }
+}
@@ -1,13 +1,14 @@
+use std::borrow::Cow;
+
 // nom
-use nom::bytes::complete::tag;
 use nom::combinator::{map, opt};
-use nom::multi::many0;
 use nom::sequence::{delimited, preceded, terminated, tuple};
 use nom::IResult;
 
 // def
-use super::base::{number, tstring, ws};
-use super::def_types::ScanChain;
+use super::base::{number, tag, tstring, ws};
+use super::common::members;
+use super::def_types::{ScanCell, ScanChain};
 
 pub fn scanchain_section(
     input: &str,
@@ -15,99 +16,80 @@ pub fn scanchain_section(
     &str,
     (
         i32, // number of scanchain
-        Vec<ScanChain>,
+        Vec<ScanChain<'_>>,
     ),
 > {
     delimited(
         tag("SCANCHAINS"),
-        tuple((terminated(number, ws(tag(";"))), many0(scanchain_member))),
+        tuple((terminated(number, ws(tag(";"))), members(scanchain_member))),
         ws(tag("END SCANCHAINS")),
     )(input)
 }
 
-fn scanchain_member(input: &str) -> IResult<&str, ScanChain> {
-    delimited(
-        tag("-"),
-        tuple((
-            tstring, // name
-            opt(preceded(
-                ws(tag("+ PARTITION")),
-                tuple((tstring, opt(preceded(tag("MAXBITS"), number)))),
-            )),
-            opt(preceded(
-                ws(tag("+ COMMONSCANPINS")),
-                tuple((
-                    delimited(ws(tag("(")), preceded(tag("IN"), tstring), ws(tag(")"))),
-                    delimited(ws(tag("(")), preceded(tag("OUT"), tstring), ws(tag(")"))),
-                )),
-            )), // commonscanpin
-            opt(preceded(
-                ws(tag("+ START")),
-                tuple((
-                    map(tstring, |n| match n {
-                        "PIN" => None,
-                        _ => Some(n),
-                    }),
-                    tstring,
+pub(crate) fn scanchain_member(input: &str) -> IResult<&str, ScanChain<'_>> {
+    map(
+        delimited(
+            tag("-"),
+            tuple((
+                tstring, // name
+                opt(preceded(
+                    ws(tag("+ PARTITION")),
+                    tuple((tstring, opt(preceded(tag("MAXBITS"), number)))),
                 )),
-            )), // start
-            opt(preceded(
-                ws(tag("+ FLOATING")),
-                tuple((
-                    tstring,
-                    delimited(ws(tag("(")), preceded(tag("IN"), tstring), ws(tag(")"))),
-                    delimited(ws(tag("(")), preceded(tag("OUT"), tstring), ws(tag(")"))),
-                    opt(delimited(
-                        ws(tag("(")),
-                        preceded(tag("BITS"), number),
-                        ws(tag(")")),
+                opt(preceded(
+                    ws(tag("+ COMMONSCANPINS")),
+                    tuple((
+                        delimited(ws(tag("(")), preceded(tag("IN"), tstring), ws(tag(")"))),
+                        delimited(ws(tag("(")), preceded(tag("OUT"), tstring), ws(tag(")"))),
                     )),
-                )),
-            )), // floating
-            opt(preceded(
-                ws(tag("+ ORDERED")),
-                tuple((
-                    tstring,
-                    delimited(ws(tag("(")), preceded(tag("IN"), tstring), ws(tag(")"))),
-                    delimited(ws(tag("(")), preceded(tag("OUT"), tstring), ws(tag(")"))),
-                    opt(delimited(
-                        ws(tag("(")),
-                        preceded(tag("BITS"), number),
-                        ws(tag(")")),
-                    )),
-                )),
-            )), // ordered
-            opt(preceded(
-                ws(tag("+ STOP")),
-                tuple((
-                    map(tstring, |n| match n {
-                        "PIN" => None,
-                        _ => Some(n),
-                    }),
-                    tstring,
-                )),
-            )), // stop
-        )),
-        ws(tag(";")),
+                )), // commonscanpin
+                opt(preceded(ws(tag("+ START")), scan_point)), // start
+                opt(preceded(ws(tag("+ FLOATING")), scan_cell)), // floating
+                opt(preceded(ws(tag("+ ORDERED")), scan_cell)), // ordered
+                opt(preceded(ws(tag("+ STOP")), scan_point)),  // stop
+            )),
+            ws(tag(";")),
+        ),
+        |n| ScanChain {
+            name: n.0.into(),
+            partition: n.1.map(|n| (n.0.into(), n.1)),
+            common_scan_pins: n.2.map(|n| (n.0.into(), n.1.into())),
+            start: n.3,
+            floating: n.4,
+            ordered: n.5,
+            stop: n.6,
+        },
     )(input)
 }
 
-// #[cfg(test)]
-// mod tests {
-
-//     use crate::collection::scanchain_parser::*;
-//     use std::io::Read;
-
-//     #[test]
-//     fn test_scanchain_section() {
-//         let mut input_def = std::fs::File::open("tests/scanchain_test.def").unwrap();
-//         let mut data = String::new();
-//         input_def.read_to_string(&mut data).unwrap();
-//         let result = scanchain_section(&data).unwrap();
-
-//         let scanchain_section = result.1;
+// { fixedInComp | PIN } [outPin]
+fn scan_point(input: &str) -> IResult<&str, (Option<Cow<'_, str>>, Cow<'_, str>)> {
+    tuple((
+        map(tstring, |n| match n {
+            "PIN" => None,
+            _ => Some(n.into()),
+        }),
+        map(tstring, Cow::from),
+    ))(input)
+}
 
-//         let num = scanchain_section.0;
-//         let ndrs = scanchain_section.1;
-//     }
-// }
+fn scan_cell(input: &str) -> IResult<&str, ScanCell<'_>> {
+    map(
+        tuple((
+            tstring,
+            delimited(ws(tag("(")), preceded(tag("IN"), tstring), ws(tag(")"))),
+            delimited(ws(tag("(")), preceded(tag("OUT"), tstring), ws(tag(")"))),
+            opt(delimited(
+                ws(tag("(")),
+                preceded(tag("BITS"), number),
+                ws(tag(")")),
+            )),
+        )),
+        |n| ScanCell {
+            name: n.0.into(),
+            in_pin: n.1.into(),
+            out_pin: n.2.into(),
+            bits: n.3,
+        },
+    )(input)
+}
@@ -1,12 +1,12 @@
 // nom
-use nom::bytes::complete::tag;
+use nom::combinator::map;
 use nom::multi::many0;
 use nom::sequence::{delimited, preceded, terminated, tuple};
 use nom::IResult;
 
 // def
-use super::base::{number, tstring, ws};
-use super::common::rect_or_polygon;
+use super::base::{number, tag, tstring, ws};
+use super::common::{members, rect_or_polygon};
 use super::def_types::Slot;
 
 pub fn slot_section(
@@ -15,63 +15,26 @@ pub fn slot_section(
     &str,
     (
         i32, // number of slot
-        Vec<Slot>,
+        Vec<Slot<'_>>,
     ),
 > {
     delimited(
         tag("SLOTS"),
-        tuple((terminated(number, ws(tag(";"))), many0(slot_member))),
+        tuple((terminated(number, ws(tag(";"))), members(slot_member))),
         tag("END SLOTS"),
     )(input)
 }
 
-fn slot_member(input: &str) -> IResult<&str, Slot> {
-    delimited(
-        tag("-"),
-        tuple((preceded(ws(tag("LAYER")), tstring), many0(rect_or_polygon))),
-        ws(tag(";")),
+pub(crate) fn slot_member(input: &str) -> IResult<&str, Slot<'_>> {
+    map(
+        delimited(
+            tag("-"),
+            tuple((preceded(ws(tag("LAYER")), tstring), many0(rect_or_polygon))),
+            ws(tag(";")),
+        ),
+        |n| Slot {
+            layer: n.0.into(),
+            shapes: n.1,
+        },
     )(input)
 }
-
-// #[cfg(test)]
-// mod tests {
-//     use super::def_types::*;
-//     use super::slot_parser::*;
-//     use std::io::Read;
-
-//     #[test]
-//     fn test_slot_section() {
-//         let mut input_def = std::fs::File::open("tests/slot_test.def").unwrap();
-//         let mut data = String::new();
-//         input_def.read_to_string(&mut data).unwrap();
-//         let result = slot_section(&data).unwrap();
-
-//         let slot_section = result.1;
-
-//         let num = slot_section.0;
-//         let slots = slot_section.1;
-
-//         assert_eq!(num, 3);
-//         assert_eq!(
-//             slots,
-//             vec![
-//                 ("M1", vec![Geometry::Rect(((3, 3), (6, 8)))],),
-//                 (
-//                     "M2",
-//                     vec![
-//                         Geometry::Rect(((3, 3), (6, 8))),
-//                         Geometry::Polygon(vec![
-//                             (0, 0),
-//                             (0, 10),
-//                             (10, 10),
-//                             (10, 20),
-//                             (20, 20),
-//                             (20, 0)
-//                         ])
-//                     ],
-//                 ),
-//                 ("M3", vec![Geometry::Rect(((3, 3), (6, 8)))])
-//             ]
-//         );
-//     }
-// }
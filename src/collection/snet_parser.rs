@@ -1,111 +1,293 @@
+use std::borrow::Cow;
+
 // nom
 use nom::branch::alt;
-use nom::bytes::complete::tag;
 use nom::combinator::{map, opt};
 use nom::multi::{many0, many1};
 
-use nom::sequence::{delimited, pair, preceded, terminated, tuple};
+use nom::sequence::{delimited, preceded, terminated, tuple};
 use nom::IResult;
 
 // def
-use super::base::{number, tstring, ws};
-use super::common::{properties, pt_list, rect, route_body};
-use super::def_types::{SNet, SNetProperty, SpecialWireBasic, SpecialWireStmt};
+use super::base::{number, tag, tstring, ws};
+use super::common::{attributes, members, net_connection, property, pt_list, rect, route_body};
+use super::def_types::{
+    NetUse, Pattern, PropValue, RoutingStatus, SNet, ShapeType, Source, SpecialWireBasic,
+    SpecialWireStmt,
+};
 use super::encoder::{
-    net_pattern_encode, snet_global_attribute_encode, snet_shape_encode, source_type_encode,
-    use_mode_encode,
+    net_pattern_encode, orient_encode, snet_global_attribute_encode, snet_shape_encode,
+    source_type_encode, use_mode_encode,
 };
 
-pub fn snet_section(input: &str) -> IResult<&str, (i32, Vec<SNet>)> {
+pub fn snet_section(input: &str) -> IResult<&str, (i32, Vec<SNet<'_>>)> {
     delimited(
         tag("SPECIALNETS"),
         tuple((
             terminated(number, ws(tag(";"))), // numSNet
-            many0(snet_member),
+            members(snet_member),
         )),
-        tag("END SPECIALNETS"),
+        ws(tag("END SPECIALNETS")),
     )(input)
 }
 
-fn snet_member(input: &str) -> IResult<&str, SNet> {
-    delimited(
-        tag("-"),
-        pair(
+// `+` attributes of a special net, in any order
+enum SNetAttribute<'a> {
+    Width((Cow<'a, str>, i32)),
+    Voltage(i32),
+    Wiring(SpecialWireStmt<'a>),
+    Source(Source),
+    FixedBump,
+    Original(Cow<'a, str>),
+    Use(NetUse),
+    Pattern(Pattern),
+    EstCap(i32),
+    Weight(i32),
+    Property((Cow<'a, str>, PropValue<'a>)),
+}
+
+impl SNetAttribute<'_> {
+    // keyword of an attribute given at most once
+    fn once(&self) -> Option<&'static str> {
+        match self {
+            SNetAttribute::Width(_) | SNetAttribute::Wiring(_) | SNetAttribute::Property(_) => None,
+            SNetAttribute::Voltage(_) => Some("+ VOLTAGE"),
+            SNetAttribute::Source(_) => Some("+ SOURCE"),
+            SNetAttribute::FixedBump => Some("+ FIXEDBUMP"),
+            SNetAttribute::Original(_) => Some("+ ORIGINAL"),
+            SNetAttribute::Use(_) => Some("+ USE"),
+            SNetAttribute::Pattern(_) => Some("+ PATTERN"),
+            SNetAttribute::EstCap(_) => Some("+ ESTCAP"),
+            SNetAttribute::Weight(_) => Some("+ WEIGHT"),
+        }
+    }
+}
+
+fn snet_attribute(input: &str) -> IResult<&str, SNetAttribute<'_>> {
+    alt((
+        map(
+            preceded(ws(tag("+ WIDTH")), tuple((tstring, number))),
+            |(layer, width)| SNetAttribute::Width((layer.into(), width)),
+        ),
+        map(
+            preceded(ws(tag("+ VOLTAGE")), number),
+            SNetAttribute::Voltage,
+        ),
+        map(source_type_encode, SNetAttribute::Source),
+        map(ws(tag("+ FIXEDBUMP")), |_| SNetAttribute::FixedBump),
+        map(preceded(ws(tag("+ ORIGINAL")), tstring), |n| {
+            SNetAttribute::Original(n.into())
+        }),
+        map(use_mode_encode, SNetAttribute::Use),
+        map(net_pattern_encode, SNetAttribute::Pattern),
+        map(preceded(ws(tag("+ ESTCAP")), number), SNetAttribute::EstCap),
+        map(preceded(ws(tag("+ WEIGHT")), number), SNetAttribute::Weight),
+        map(property, SNetAttribute::Property),
+        // "+" followed by a routing status, after the attributes starting with a keyword
+        map(special_wiring, SNetAttribute::Wiring),
+    ))(input)
+}
+
+pub(crate) fn snet_member(input: &str) -> IResult<&str, SNet<'_>> {
+    map(
+        delimited(
+            tag("-"),
             tuple((
                 tstring,
-                many0(delimited(
-                    ws(tag("(")),
-                    tuple((
-                        map(tstring, |n| match n {
-                            "PIN" => None,
-                            n => Some(n),
-                        }),
-                        tstring,
-                        map(
-                            opt(ws(tag("+ SYNTHESIZED"))),
-                            |res: Option<&str>| match res {
-                                Some(_) => true,
-                                None => false,
-                            },
-                        ),
-                    )),
-                    ws(tag(")")),
-                )),
-            )),
-            tuple((
-                opt(preceded(ws(tag("+ VOLTAGE")), number)),
-                many0(special_wiring),
-                snet_property,
+                many0(net_connection),
+                attributes(snet_attribute, SNetAttribute::once),
             )),
+            ws(tag(";")),
         ),
-        ws(tag(";")),
+        |(name, connections, attrs)| {
+            let mut snet = SNet {
+                name: name.into(),
+                connections,
+                widths: Vec::new(),
+                voltage: None,
+                wiring: Vec::new(),
+                source: None,
+                fixed_bump: false,
+                original: None,
+                use_mode: None,
+                pattern: None,
+                est_cap: None,
+                weight: None,
+                properties: Vec::new(),
+            };
+            for attr in attrs {
+                match attr {
+                    SNetAttribute::Width(n) => snet.widths.push(n),
+                    SNetAttribute::Voltage(n) => snet.voltage = Some(n),
+                    SNetAttribute::Wiring(n) => snet.wiring.push(n),
+                    SNetAttribute::Source(n) => snet.source = Some(n),
+                    SNetAttribute::FixedBump => snet.fixed_bump = true,
+                    SNetAttribute::Original(n) => snet.original = Some(n),
+                    SNetAttribute::Use(n) => snet.use_mode = Some(n),
+                    SNetAttribute::Pattern(n) => snet.pattern = Some(n),
+                    SNetAttribute::EstCap(n) => snet.est_cap = Some(n),
+                    SNetAttribute::Weight(n) => snet.weight = Some(n),
+                    SNetAttribute::Property(n) => snet.properties.push(n),
+                }
+            }
+            snet
+        },
+    )(input)
+}
+
+fn special_wire_basic(input: &str) -> IResult<&str, SpecialWireBasic<'_>> {
+    map(
+        tuple((
+            tstring,
+            number,
+            opt(snet_shape_encode),
+            opt(preceded(ws(tag("+ STYLE")), number)),
+            route_body,
+        )),
+        |n| SpecialWireBasic {
+            layer: n.0.into(),
+            width: n.1,
+            shape: n.2,
+            style: n.3,
+            route: n.4,
+        },
     )(input)
 }
 
-fn special_wire_basic(input: &str) -> IResult<&str, SpecialWireBasic> {
+// [+ COVER | + FIXED | + ROUTED | + SHIELD shieldNetName] [+ SHAPE shape] [+ MASK maskNum] before
+// + POLYGON, + RECT and + VIA
+#[allow(clippy::type_complexity)]
+fn special_shape_prefix(
+    input: &str,
+) -> IResult<
+    &str,
+    (
+        Option<(RoutingStatus, Option<Cow<'_, str>>)>,
+        Option<ShapeType>,
+        Option<i32>,
+    ),
+> {
     tuple((
-        tstring,
-        number,
+        opt(special_status),
         opt(snet_shape_encode),
-        opt(preceded(ws(tag("+ STYLE")), number)),
-        route_body,
+        opt(preceded(ws(tag("+ MASK")), number)),
     ))(input)
 }
 
-fn special_wiring(input: &str) -> IResult<&str, SpecialWireStmt> {
+// + COVER, + FIXED, + ROUTED or + SHIELD shieldNetName
+fn special_status(input: &str) -> IResult<&str, (RoutingStatus, Option<Cow<'_, str>>)> {
     alt((
-        map(preceded(ws(tag("+ RECT")), tuple((tstring, rect))), |n| {
-            SpecialWireStmt::Rect(n)
+        map(preceded(ws(tag("+ SHIELD")), tstring), |n| {
+            (RoutingStatus::Shield, Some(n.into()))
         }),
+        map(snet_global_attribute_encode, |n| (n, None)),
+    ))(input)
+}
+
+fn special_wiring(input: &str) -> IResult<&str, SpecialWireStmt<'_>> {
+    alt((
         map(
-            preceded(ws(tag("+ POLYGON")), tuple((tstring, pt_list))),
-            |n| SpecialWireStmt::Polygon(n),
+            tuple((
+                special_shape_prefix,
+                preceded(ws(tag("+ RECT")), tuple((tstring, rect))),
+            )),
+            |((status, shape, mask), (layer, rect))| SpecialWireStmt::Rect {
+                status,
+                shape,
+                mask,
+                layer: layer.into(),
+                rect,
+            },
         ),
         map(
             tuple((
-                snet_global_attribute_encode,
+                special_shape_prefix,
+                preceded(ws(tag("+ POLYGON")), tuple((tstring, pt_list))),
+            )),
+            |((status, shape, mask), (layer, points))| SpecialWireStmt::Polygon {
+                status,
+                shape,
+                mask,
+                layer: layer.into(),
+                points,
+            },
+        ),
+        map(
+            tuple((
+                special_shape_prefix,
+                preceded(
+                    ws(tag("+ VIA")),
+                    tuple((tstring, opt(orient_encode), pt_list)),
+                ),
+            )),
+            |((status, shape, mask), (via, orient, points))| SpecialWireStmt::Via {
+                status,
+                shape,
+                mask,
+                via: via.into(),
+                orient,
+                points,
+            },
+        ),
+        map(
+            tuple((
+                special_status,
                 many1(alt((
                     preceded(ws(tag("NEW")), special_wire_basic),
                     special_wire_basic,
                 ))),
             )),
-            |n| SpecialWireStmt::Route(n),
+            |((status, shield_net), wires)| SpecialWireStmt::Route {
+                status,
+                shield_net,
+                wires,
+            },
         ),
     ))(input)
 }
 
-fn snet_property(input: &str) -> IResult<&str, SNetProperty> {
-    tuple((
-        opt(source_type_encode),
-        map(opt(ws(tag("+ FIXEDBUMP"))), |res: Option<&str>| match res {
-            Some(_) => true,
-            None => false,
-        }),
-        opt(preceded(ws(tag("+ ORIGINAL")), tstring)),
-        opt(use_mode_encode),
-        opt(net_pattern_encode),
-        opt(preceded(ws(tag("+ ESTCAP")), number)),
-        opt(preceded(ws(tag("+ WEIGHT")), number)),
-        properties,
-    ))(input)
+#[cfg(test)]
+mod tests {
+    use crate::collection::def_types::{RouteElem, SpecialWireStmt};
+    use crate::collection::snet_parser::*;
+    use std::io::Read;
+
+    #[test]
+    fn test_snet_section() {
+        let mut input_def = std::fs::File::open("tests/snet_test.def").unwrap();
+        let mut data = String::new();
+        input_def.read_to_string(&mut data).unwrap();
+        let (remain, (num, snets)) = snet_section(&data).unwrap();
+        assert_eq!(remain.trim(), "");
+        assert_eq!(num, 5);
+        let names: Vec<&str> = snets.iter().map(|n| &*n.name).collect();
+        assert_eq!(names, vec!["SN1", "VDD", "SN2", "SN3", "DUMMY", "DUMMY2"]);
+        assert_eq!(snets[0].connections[3].component.as_deref(), Some("*"));
+        assert_eq!(
+            snets[0].widths,
+            vec![("METAL1".into(), 200), ("M2".into(), 300)]
+        );
+
+        let wires = match &snets[0].wiring[0] {
+            SpecialWireStmt::Route { wires, .. } => wires,
+            n => panic!("not a route: {:?}", n),
+        };
+        assert_eq!(wires.len(), 19);
+        // a via right after another via
+        let vias: Vec<_> = wires[5]
+            .route
+            .iter()
+            .filter_map(|n| match n {
+                RouteElem::Via { point, via, .. } => Some((point.is_some(), &**via)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(vias, vec![(true, "CUSTOMVIA"), (false, "VIAGEN12_1")]);
+        let vias = wires[15]
+            .route
+            .iter()
+            .filter(|n| matches!(n, RouteElem::Via { .. }))
+            .count();
+        assert_eq!(vias, 2);
+    }
 }
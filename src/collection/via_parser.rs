@@ -1,15 +1,16 @@
+use std::borrow::Cow;
+
 // nom
 use nom::branch::alt;
-use nom::bytes::complete::tag;
 use nom::combinator::{map, opt};
 use nom::multi::many0;
 use nom::sequence::{delimited, pair, preceded, terminated, tuple};
 use nom::IResult;
 
 // def
-use super::base::{number, tstring, ws};
-use super::common::{pt_list, rect};
-use super::def_types::{Geometry, Via, ViaBody};
+use super::base::{number, tag, tstring, ws};
+use super::common::{members, pt_list, rect};
+use super::def_types::{GeneratedVia, Geometry, Via, ViaBody};
 
 pub fn via_section(
     input: &str,
@@ -17,79 +18,82 @@ pub fn via_section(
     &str,
     (
         i32, // viaNum
-        Vec<Via>,
+        Vec<Via<'_>>,
     ),
 > {
     delimited(
         tag("VIAS"),
         tuple((
             terminated(number, ws(tag(";"))), // numVia
-            many0(via_member),
+            members(via_member),
         )),
         tag("END VIAS"),
     )(input)
 }
 
-fn via_member(input: &str) -> IResult<&str, Via> {
-    delimited(
-        tag("-"),
-        pair(
-            tstring,
-            alt((
-                map(
-                    tuple((
-                        preceded(ws(tag("+ VIARULE")), tstring),
-                        preceded(ws(tag("+ CUTSIZE")), tuple((number, number))),
-                        preceded(ws(tag("+ LAYERS")), tuple((tstring, tstring, tstring))),
-                        preceded(ws(tag("+ CUTSPACING")), tuple((number, number))),
-                        preceded(
-                            ws(tag("+ ENCLOSURE")),
-                            tuple((number, number, number, number)),
-                        ),
-                        opt(preceded(ws(tag("+ ROWCOL")), tuple((number, number)))),
-                        opt(preceded(ws(tag("+ ORIGIN")), tuple((number, number)))),
-                        opt(preceded(
-                            ws(tag("+ OFFSET")),
-                            tuple((number, number, number, number)),
-                        )),
-                        opt(preceded(ws(tag("+ PATTERN")), tstring)),
-                    )),
-                    |n| ViaBody::Generated(n),
-                ),
-                map(
-                    many0(alt((
-                        tuple((
-                            preceded(ws(tag("+ RECT")), tstring),
-                            map(rect, |u| Geometry::Rect(u)),
-                        )),
+pub(crate) fn via_member(input: &str) -> IResult<&str, Via<'_>> {
+    map(
+        delimited(
+            tag("-"),
+            pair(
+                tstring,
+                alt((
+                    map(
                         tuple((
-                            preceded(ws(tag("+ POLYGON")), tstring),
-                            map(pt_list, |u| Geometry::Polygon(u)),
+                            preceded(ws(tag("+ VIARULE")), tstring),
+                            preceded(ws(tag("+ CUTSIZE")), tuple((number, number))),
+                            preceded(ws(tag("+ LAYERS")), tuple((tstring, tstring, tstring))),
+                            preceded(ws(tag("+ CUTSPACING")), tuple((number, number))),
+                            preceded(
+                                ws(tag("+ ENCLOSURE")),
+                                tuple((number, number, number, number)),
+                            ),
+                            opt(preceded(ws(tag("+ ROWCOL")), tuple((number, number)))),
+                            opt(preceded(ws(tag("+ ORIGIN")), tuple((number, number)))),
+                            opt(preceded(
+                                ws(tag("+ OFFSET")),
+                                tuple((number, number, number, number)),
+                            )),
+                            opt(preceded(ws(tag("+ PATTERN")), tstring)),
                         )),
-                    ))),
-                    |n| ViaBody::Fixed(n),
-                ),
-            )),
+                        |n| {
+                            ViaBody::Generated(GeneratedVia {
+                                via_rule: n.0.into(),
+                                cut_size: n.1,
+                                bot_layer: (n.2).0.into(),
+                                cut_layer: (n.2).1.into(),
+                                top_layer: (n.2).2.into(),
+                                cut_spacing: n.3,
+                                enclosure: n.4,
+                                row_col: n.5,
+                                origin: n.6,
+                                offset: n.7,
+                                pattern: n.8.map(Cow::from),
+                            })
+                        },
+                    ),
+                    map(
+                        many0(alt((
+                            tuple((
+                                map(preceded(ws(tag("+ RECT")), tstring), Cow::from),
+                                opt(preceded(ws(tag("+ MASK")), number)),
+                                map(rect, Geometry::Rect),
+                            )),
+                            tuple((
+                                map(preceded(ws(tag("+ POLYGON")), tstring), Cow::from),
+                                opt(preceded(ws(tag("+ MASK")), number)),
+                                map(pt_list, Geometry::Polygon),
+                            )),
+                        ))),
+                        ViaBody::Fixed,
+                    ),
+                )),
+            ),
+            ws(tag(";")),
         ),
-        ws(tag(";")),
+        |n| Via {
+            name: n.0.into(),
+            body: n.1,
+        },
     )(input)
 }
-
-// #[cfg(test)]
-// mod tests {
-
-//     use super::via_parser::*;
-//     use std::io::Read;
-
-//     #[test]
-//     fn test_via_section() {
-//         let mut input_def = std::fs::File::open("tests/via_test.def").unwrap();
-//         let mut data = String::new();
-//         input_def.read_to_string(&mut data).unwrap();
-//         let result = via_section(&data).unwrap();
-//         let via_section = result.1;
-//         let num = via_section.0;
-//         let _vias = via_section.1;
-//         assert_eq!(num, 6);
-//     }
-// }
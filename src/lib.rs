@@ -1,9 +1,16 @@
 extern crate nom;
 
+pub use collection::base::unescape;
 pub use collection::blockage_parser::blockage_section;
 pub use collection::component_parser::component_section;
+pub use collection::def_error::{DefError, Diagnostic, ReadError, Severity};
+pub use collection::def_owned::IntoOwned;
+pub use collection::def_parser::{parse_def, parse_def_with, CountCheck, ParseOptions, ParsedDef};
+pub use collection::def_reader::{read_def, read_def_from, DefVisitor};
 pub use collection::def_types::*;
+pub use collection::def_writer::{def_to_string, write_def};
 pub use collection::design_config_parser::design_config;
+pub use collection::extension_parser::extension_section;
 pub use collection::fill_parser::fill_section;
 pub use collection::group_parser::group_section;
 pub use collection::header_parser::header_section;